@@ -3,12 +3,9 @@ use std::fs::{self};
 use std::io::BufReader;
 use std::process::ExitCode;
 
-use bitvec::prelude::*;
 use clap::Parser;
 use itertools::Itertools;
 use klv_uas::klv_packet::KlvPacket;
-use klv_uas::klv_value::KlvValue;
-use klv_uas::tag::Tag;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
@@ -114,24 +111,8 @@ fn main() -> ExitCode {
                 continue;
             };
 
-            // Check if the KLV packet even has a generic flag field
-            let Some(generic_flag) = klv.get(Tag::GenericFlagData) else {
-                continue;
-            };
-
-            // Get the value of the generic flag
-            let value = match generic_flag.value() {
-                KlvValue::Uint8(value) => value,
-                _ => panic!("Can't get value for generic flag"),
-            };
-
-            debug!("Generic flag found: [{:#b}]", value);
-
-            // Get the individual bits for the flag
-            let bits: &BitSlice<_, Lsb0> = BitSlice::from_element(value);
-
-            // The laser flag is the in the zero'th position
-            if bits[0] {
+            // The laser flag is stored in the generic flag data
+            if klv.generic_flags().is_some_and(|flags| flags.laser_on()) {
                 info!("Laser is on for file {}", filename);
                 is_laser_video = true;
                 break;
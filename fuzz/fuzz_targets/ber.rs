@@ -0,0 +1,21 @@
+#![no_main]
+
+use klv_uas::cursor::Cursor;
+use klv_uas::local_set::Encoding;
+use klv_uas::local_set::parse_triplet;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    for encoding in [
+        Encoding::Ber,
+        Encoding::BerOid,
+        Encoding::Fixed(1),
+        Encoding::Fixed(2),
+        Encoding::Fixed(4),
+    ] {
+        let mut buf = Cursor::new(data);
+        while encoding.read(&mut buf).is_ok() {}
+    }
+
+    let _ = parse_triplet(data);
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use klv_uas::local_set::Registry;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let registry = Registry::with_defaults();
+    if let Ok(Some(set)) = registry.parse(data) {
+        for field in set.fields() {
+            let _ = field.value();
+        }
+    }
+});
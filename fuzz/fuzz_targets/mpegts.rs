@@ -0,0 +1,16 @@
+#![no_main]
+
+use klv_uas::mpegts::TsDemuxer;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut demuxer = TsDemuxer::new();
+    // Treat every PID carried in the first packet as KLV so the PES
+    // reassembly is reached without a valid Program Map Table.
+    if let [_, hi, lo, ..] = data {
+        demuxer.add_pid(u16::from_be_bytes([hi & 0x1F, *lo]));
+    }
+    demuxer.push(data);
+    demuxer.finish();
+    while demuxer.pop().is_some() {}
+});
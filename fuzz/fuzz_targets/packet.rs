@@ -0,0 +1,32 @@
+#![no_main]
+
+use klv_uas::klv_packet::KlvPacket;
+use klv_uas::local_set::ChecksumPolicy;
+use klv_uas::options::ParseOptions;
+use klv_uas::options::UnimplementedPolicy;
+use klv_uas::options::UnknownTagPolicy;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = KlvPacket::from_bytes(data);
+
+    // Accept as much as possible so the value decoders see every input.
+    let options = ParseOptions {
+        search: true,
+        unknown_tags: UnknownTagPolicy::Skip,
+        checksum: ChecksumPolicy::Lenient,
+        unimplemented: UnimplementedPolicy::Keep,
+        ..ParseOptions::default()
+    };
+    if let Ok(Some((packet, _))) = KlvPacket::parse_with(data, &options) {
+        for field in packet.fields() {
+            let _ = field.value().to_string();
+        }
+        let _ = packet.sensor_location();
+        let _ = packet.frame_center();
+        let _ = packet.weapon_stores();
+        let _ = packet.miis_core_identifier();
+        let _ = packet.sdcc().map(|sdcc| sdcc.covariance_matrix());
+        let _ = packet.duplicates();
+    }
+});
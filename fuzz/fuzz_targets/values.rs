@@ -0,0 +1,20 @@
+#![no_main]
+
+use klv_uas::klv_value::KlvValue;
+use klv_uas::miis::MiisCoreId;
+use klv_uas::sdcc::Sdcc;
+use klv_uas::tag::Tag;
+use klv_uas::weapon::WeaponStore;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // The first byte picks the tag whose decoder is used for the rest.
+    let Some((tag, value)) = data.split_first() else {
+        return;
+    };
+    let _ = KlvValue::from_bytes(Tag::from(*tag as usize), value);
+
+    let _ = Sdcc::from_bytes(value);
+    let _ = WeaponStore::from_series(value);
+    let _ = MiisCoreId::from_bytes(value);
+});
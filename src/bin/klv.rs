@@ -0,0 +1,606 @@
+//! Command line tool for inspecting UAS LS KLV metadata.
+//!
+//! Input is either a raw stream of KLV packets or an MPEG-2 transport stream,
+//! read from a file or from stdin when no file (or `-`) is given. Transport
+//! streams are recognised by their leading sync byte.
+use std::fs::File;
+use std::fs::{self};
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Write;
+use std::io::{self};
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use clap::Args;
+use clap::Parser;
+use clap::Subcommand;
+use clap::ValueEnum;
+use klv_uas::ErrorKind;
+use klv_uas::export;
+use klv_uas::generator::Faults;
+use klv_uas::generator::FlightPattern;
+use klv_uas::generator::Generator;
+use klv_uas::generator::Pointing;
+use klv_uas::generator::Scenario;
+use klv_uas::generator::Sensor;
+use klv_uas::klv_packet::KlvPacket;
+use klv_uas::klv_packet::UAS_LOCAL_SET_UNIVERSAL_LABEL;
+use klv_uas::klv_value::KlvValue;
+use klv_uas::local_set::parse_triplet;
+use klv_uas::mpegts::KlvDemuxer;
+use klv_uas::rules::RuleSet;
+use klv_uas::tag::Tag;
+
+/// Size of the chunks that raw KLV input is read in.
+const CHUNK_SIZE: usize = 8192;
+/// Longest triplet the raw reader will buffer while waiting for the rest of
+/// it. Longer ones are treated as noise.
+const MAX_TRIPLET_SIZE: usize = 1 << 20;
+
+/// The first bytes of every SMPTE universal key.
+const KEY_PREFIX: [u8; 4] = [0x06, 0x0E, 0x2B, 0x34];
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print packets in a human readable form.
+    Dump(Filter),
+    /// Write the raw bytes of the packets that pass the filter.
+    Filter(Filter),
+    /// Convert packets into another format.
+    Convert {
+        /// Format to convert into.
+        #[arg(long)]
+        to: Format,
+
+        #[command(flatten)]
+        filter: Filter,
+    },
+    /// Check files against a file of rules, printing the intervals where each
+    /// rule held.
+    Sort {
+        /// File of rules, one per line.
+        #[arg(short, long)]
+        rules: PathBuf,
+
+        /// Move each file into a directory named after the first rule it
+        /// matches, inside this directory.
+        #[arg(long)]
+        move_to: Option<PathBuf>,
+
+        /// PID of a KLV stream in a transport stream. Streams described in
+        /// the Program Map Table are always read.
+        #[arg(short, long)]
+        pid: Vec<u16>,
+
+        /// Files to check.
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Write raw KLV packets for a simulated flight.
+    Generate(Generate),
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Format {
+    /// One JSON object per packet.
+    Json,
+    /// One row per packet.
+    Csv,
+    /// The path of the sensor.
+    Kml,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Pattern {
+    /// Circles around the point.
+    Orbit,
+    /// A racetrack centred on the point.
+    Racetrack,
+    /// A straight line from the point.
+    Line,
+}
+
+#[derive(Args, Debug)]
+struct Generate {
+    /// Number of packets to write. Writes forever if not given.
+    #[arg(short, long)]
+    count: Option<u64>,
+
+    /// Packets per second of flight.
+    #[arg(long, default_value_t = 30.0)]
+    rate: f64,
+
+    /// Write the packets at the rate they were generated rather than as fast
+    /// as possible.
+    #[arg(long)]
+    realtime: bool,
+
+    /// Precision time stamp of the first packet, in microseconds since the
+    /// epoch. Defaults to the current time.
+    #[arg(long)]
+    start: Option<u64>,
+
+    #[arg(long, value_enum, default_value_t = Pattern::Orbit)]
+    pattern: Pattern,
+
+    /// Latitude of the point the pattern is flown around, in degrees.
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+    latitude: f64,
+
+    /// Longitude of the point the pattern is flown around, in degrees.
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+    longitude: f64,
+
+    /// Radius of the turns, in metres.
+    #[arg(long, default_value_t = 2000.0)]
+    radius: f64,
+
+    /// Length of the straight legs of a racetrack, in metres.
+    #[arg(long, default_value_t = 5000.0)]
+    length: f64,
+
+    /// Heading of a racetrack or line, in degrees.
+    #[arg(long, default_value_t = 0.0)]
+    heading: f64,
+
+    /// Altitude above mean sea level, in metres.
+    #[arg(long, default_value_t = 1500.0)]
+    altitude: f64,
+
+    /// Ground speed, in metres per second.
+    #[arg(long, default_value_t = 40.0)]
+    speed: f64,
+
+    /// Stare at the point rather than looking out of the right side.
+    #[arg(long)]
+    stare: bool,
+
+    /// How far the sensor slews either side, in degrees.
+    #[arg(long, default_value_t = 0.0)]
+    slew: f64,
+
+    /// Probability of a packet having a bad checksum.
+    #[arg(long, default_value_t = 0.0)]
+    bad_checksum: f64,
+
+    /// Probability of a packet being cut short.
+    #[arg(long, default_value_t = 0.0)]
+    truncate: f64,
+
+    /// Probability of each tag being left out of a packet.
+    #[arg(long, default_value_t = 0.0)]
+    drop_tag: f64,
+
+    /// Seed for the faults.
+    #[arg(long, default_value_t = 0x5EED)]
+    seed: u64,
+}
+
+impl Generate {
+    fn scenario(&self) -> Scenario {
+        let (latitude, longitude) = (self.latitude, self.longitude);
+        let pattern = match self.pattern {
+            Pattern::Orbit => FlightPattern::Orbit {
+                latitude,
+                longitude,
+                radius: self.radius,
+            },
+            Pattern::Racetrack => FlightPattern::Racetrack {
+                latitude,
+                longitude,
+                length: self.length,
+                radius: self.radius,
+                heading: self.heading,
+            },
+            Pattern::Line => FlightPattern::Line {
+                latitude,
+                longitude,
+                heading: self.heading,
+            },
+        };
+        let pointing = if self.stare {
+            Pointing::Target { latitude, longitude }
+        } else {
+            Sensor::default().pointing
+        };
+        let start = self.start.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |now| now.as_micros() as u64)
+        });
+
+        Scenario {
+            pattern,
+            altitude: self.altitude,
+            speed: self.speed,
+            rate: self.rate,
+            start,
+            sensor: Sensor { pointing, slew: self.slew, ..Sensor::default() },
+            faults: Faults {
+                bad_checksum: self.bad_checksum,
+                truncate: self.truncate,
+                drop_tag: self.drop_tag,
+            },
+            seed: self.seed,
+            ..Scenario::default()
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+struct Filter {
+    /// File to read. Reads from stdin if not given or `-`.
+    input: Option<PathBuf>,
+
+    /// PID of a KLV stream in a transport stream. Streams described in the
+    /// Program Map Table are always read.
+    #[arg(short, long)]
+    pid: Vec<u16>,
+
+    /// Only output these tags, by ID or name.
+    #[arg(short, long, value_parser = parse_tag)]
+    tag: Vec<Tag>,
+
+    /// Only keep packets containing all of these tags, by ID or name.
+    #[arg(long, value_parser = parse_tag)]
+    has_tag: Vec<Tag>,
+
+    /// Skip packets with a precision time stamp before this, in microseconds
+    /// since the epoch.
+    #[arg(long)]
+    start: Option<u64>,
+
+    /// Skip packets with a precision time stamp after this, in microseconds
+    /// since the epoch.
+    #[arg(long)]
+    end: Option<u64>,
+}
+
+impl Filter {
+    fn matches(&self, packet: &KlvPacket) -> bool {
+        if !self.has_tag.iter().all(|tag| packet.get_ref(*tag).is_some()) {
+            return false;
+        }
+        if self.start.is_none() && self.end.is_none() {
+            return true;
+        }
+
+        let Some(KlvValue::Uint64(timestamp)) =
+            packet.get_ref(Tag::PrecisionTimeStamp).map(|field| field.value())
+        else {
+            return false;
+        };
+        self.start.is_none_or(|start| *timestamp >= start)
+            && self.end.is_none_or(|end| *timestamp <= end)
+    }
+
+    /// The tags to output, or every tag if none were chosen.
+    fn columns(&self) -> Vec<Tag> {
+        if !self.tag.is_empty() {
+            return self.tag.clone();
+        }
+        Tag::iter()
+            .filter(|tag| !matches!(*tag, Tag::Unknown | Tag::Deprecated))
+            .collect()
+    }
+}
+
+/// Parse a tag from either its ID or its name, ignoring case.
+fn parse_tag(arg: &str) -> Result<Tag, String> {
+    match arg.parse::<Tag>() {
+        Ok(Tag::Unknown | Tag::Deprecated) | Err(_) => {
+            Err(format!("Unknown tag [{}]", arg))
+        }
+        Ok(tag) => Ok(tag),
+    }
+}
+
+/// A UAS LS packet along with the bytes it was parsed from.
+struct Record {
+    raw: Box<[u8]>,
+    packet: KlvPacket,
+}
+
+/// Splits a raw stream of KLV triplets into UAS LS packets, skipping any
+/// bytes that don't belong to a triplet.
+struct RawKlvReader<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    eof: bool,
+}
+
+impl<R: Read> RawKlvReader<R> {
+    fn new(reader: R) -> RawKlvReader<R> {
+        RawKlvReader { reader, buffer: Vec::new(), eof: false }
+    }
+
+    /// Read another chunk into the buffer, returning `false` at the end of
+    /// the input.
+    fn fill(&mut self) -> io::Result<bool> {
+        let mut chunk = [0; CHUNK_SIZE];
+        loop {
+            match self.reader.read(&mut chunk) {
+                Ok(0) => return Ok(false),
+                Ok(read) => {
+                    self.buffer.extend_from_slice(&chunk[..read]);
+                    return Ok(true);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for RawKlvReader<R> {
+    type Item = Result<Record, ErrorKind>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // Drop anything before the next universal key.
+            let skip = self
+                .buffer
+                .windows(KEY_PREFIX.len())
+                .position(|window| window == KEY_PREFIX)
+                .unwrap_or(
+                    self.buffer.len().saturating_sub(KEY_PREFIX.len() - 1),
+                );
+            self.buffer.drain(..skip);
+
+            let Ok((key, _, length)) = parse_triplet(&self.buffer) else {
+                if !self.eof && self.buffer.len() < MAX_TRIPLET_SIZE {
+                    // The triplet may be incomplete so wait for more data.
+                    match self.fill() {
+                        Ok(true) => {}
+                        Ok(false) => self.eof = true,
+                        Err(e) => {
+                            self.eof = true;
+                            return Some(Err(e.into()));
+                        }
+                    }
+                    continue;
+                }
+                // No more data is coming, or the triplet is too long to
+                // buffer, so this isn't the start of a triplet.
+                if self.buffer.is_empty() {
+                    return None;
+                }
+                self.buffer.drain(..1);
+                continue;
+            };
+
+            let raw: Box<[u8]> = self.buffer.drain(..length).collect();
+            if key != UAS_LOCAL_SET_UNIVERSAL_LABEL {
+                continue;
+            }
+            match KlvPacket::from_bytes(&raw) {
+                Ok(Some(packet)) => return Some(Ok(Record { raw, packet })),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Open the input, or stdin if there is none, and iterate over the packets
+/// in it.
+fn packets(
+    input: Option<&Path>,
+    pids: &[u16],
+) -> io::Result<Box<dyn Iterator<Item = Result<Record, ErrorKind>>>> {
+    let reader: Box<dyn Read> = match input {
+        Some(path) if path.as_os_str() != "-" => Box::new(File::open(path)?),
+        _ => Box::new(io::stdin().lock()),
+    };
+    let mut reader = BufReader::new(reader);
+
+    if reader.fill_buf()?.first() != Some(&0x47) {
+        return Ok(Box::new(RawKlvReader::new(reader)));
+    }
+
+    let mut demuxer = KlvDemuxer::new(reader);
+    for pid in pids {
+        demuxer.add_pid(*pid);
+    }
+    Ok(Box::new(demuxer.map(|timed| {
+        timed.map(|timed| Record { raw: timed.raw, packet: timed.packet })
+    })))
+}
+
+fn dump(
+    out: &mut impl Write,
+    index: usize,
+    record: &Record,
+    tags: &[Tag],
+) -> io::Result<()> {
+    writeln!(out, "Packet {} ({} bytes)", index, record.raw.len())?;
+    let fields = record
+        .packet
+        .fields()
+        .filter(|field| tags.is_empty() || tags.contains(&field.tag()));
+    for field in fields {
+        writeln!(
+            out,
+            "  {:>3} {:<40} {}",
+            field.tag().id(),
+            field.tag().string(),
+            field.value()
+        )?;
+    }
+    Ok(())
+}
+
+fn run(command: &Command, out: &mut impl Write) -> io::Result<()> {
+    let (filter, format) = match command {
+        Command::Dump(filter) | Command::Filter(filter) => (filter, None),
+        Command::Convert { to, filter } => (filter, Some(*to)),
+        Command::Sort { rules, move_to, pid, files } => {
+            return sort(rules, move_to.as_deref(), pid, files, out);
+        }
+        Command::Generate(args) => return generate(args, out),
+    };
+    let columns = filter.columns();
+
+    let mut text = String::new();
+    if let Some(Format::Csv) = format {
+        export::write_csv_header(&mut text, &columns)
+            .expect("Writing to a string can't fail");
+    }
+
+    let mut track = Vec::new();
+    let records = packets(filter.input.as_deref(), &filter.pid)?
+        .filter_map(|record| {
+            record
+                .inspect_err(|e| eprintln!("Failed to parse packet: {}", e))
+                .ok()
+        })
+        .filter(|record| filter.matches(&record.packet));
+    for (index, record) in records.enumerate() {
+        match (command, format) {
+            (Command::Dump(_), _) => dump(out, index, &record, &filter.tag)?,
+            (Command::Filter(_), _) => out.write_all(&record.raw)?,
+            (_, Some(Format::Json)) => {
+                export::write_json(&mut text, &record.packet, &filter.tag)
+                    .expect("Writing to a string can't fail")
+            }
+            (_, Some(Format::Csv)) => {
+                export::write_csv_row(&mut text, &record.packet, &columns)
+                    .expect("Writing to a string can't fail")
+            }
+            (_, _) => track.push(record.packet),
+        }
+        out.write_all(text.as_bytes())?;
+        text.clear();
+    }
+
+    if let Some(Format::Kml) = format {
+        let name = match &filter.input {
+            Some(path) => path.display().to_string(),
+            None => String::from("stdin"),
+        };
+        export::write_kml(&mut text, &name, &track)
+            .expect("Writing to a string can't fail");
+    }
+    out.write_all(text.as_bytes())?;
+    out.flush()
+}
+
+fn sort(
+    rules: &Path,
+    move_to: Option<&Path>,
+    pids: &[u16],
+    files: &[PathBuf],
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let rules = fs::read_to_string(rules)?
+        .parse::<RuleSet>()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    for file in files {
+        let records = match packets(Some(file), pids) {
+            Ok(records) => records,
+            Err(e) => {
+                eprintln!("Cannot read [{}]: {}", file.display(), e);
+                continue;
+            }
+        };
+
+        let mut evaluator = rules.evaluator();
+        for record in records {
+            match record {
+                Ok(record) => evaluator.push(&record.packet),
+                Err(e) => eprintln!(
+                    "Failed to parse packet in [{}]: {}",
+                    file.display(),
+                    e
+                ),
+            }
+        }
+        let matches = evaluator.finish();
+
+        for rule in &matches {
+            write!(out, "{}\t{}\t", file.display(), rule.rule)?;
+            for (idx, interval) in rule.intervals.iter().enumerate() {
+                let separator = if idx > 0 { " " } else { "" };
+                write!(
+                    out,
+                    "{}{}-{}",
+                    separator, interval.start, interval.end
+                )?;
+            }
+            writeln!(out)?;
+        }
+
+        let (Some(move_to), Some(rule), Some(name)) =
+            (move_to, matches.first(), file.file_name())
+        else {
+            continue;
+        };
+        let directory = move_to.join(&*rule.rule);
+        if let Err(e) = fs::create_dir_all(&directory)
+            .and_then(|()| fs::rename(file, directory.join(name)))
+        {
+            eprintln!(
+                "Cannot move [{}] into [{}]: {}",
+                file.display(),
+                directory.display(),
+                e
+            );
+        }
+    }
+
+    out.flush()
+}
+
+fn generate(args: &Generate, out: &mut impl Write) -> io::Result<()> {
+    let packets = Generator::new(args.scenario())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let started = Instant::now();
+    for (index, packet) in packets.enumerate() {
+        if args.count.is_some_and(|count| index as u64 >= count) {
+            break;
+        }
+        if args.realtime {
+            let due =
+                started + Duration::from_secs_f64(index as f64 / args.rate);
+            thread::sleep(due.saturating_duration_since(Instant::now()));
+        }
+        out.write_all(&packet.bytes)?;
+        if args.realtime {
+            out.flush()?;
+        }
+    }
+
+    out.flush()
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let mut out = BufWriter::new(io::stdout().lock());
+
+    match run(&cli.command, &mut out) {
+        Ok(()) => ExitCode::SUCCESS,
+        // The reader on the other end of the pipe went away, such as `head`.
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(1)
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! A minimal stand-in for `std::io::Cursor` that is available without `std`.
+//!
+//! Only the parts of the `std` API that the parsers need are provided.
+//! Reading past the end of the data is reported as
+//! [`ErrorKind::InvalidLength`].
+use crate::ErrorKind;
+
+/// Tracks a read position within an in-memory buffer.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cursor<T> {
+    inner: T,
+    position: u64,
+}
+
+impl<T> Cursor<T> {
+    pub fn new(inner: T) -> Cursor<T> {
+        Cursor { inner, position: 0 }
+    }
+
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: u64) {
+        self.position = position;
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: AsRef<[u8]>> Cursor<T> {
+    /// Fill `buf` from the current position and advance past the bytes read.
+    pub fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), ErrorKind> {
+        let data = self.inner.as_ref();
+        let bytes = usize::try_from(self.position)
+            .ok()
+            .and_then(|start| data.get(start..start.checked_add(buf.len())?))
+            .ok_or(ErrorKind::InvalidLength)?;
+
+        buf.copy_from_slice(bytes);
+        self.position += buf.len() as u64;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cursor;
+
+    #[test]
+    fn read_exact() {
+        let mut cursor = Cursor::new([1, 2, 3]);
+        let mut buf = [0; 2];
+        cursor.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [1, 2]);
+        assert!(cursor.read_exact(&mut buf).is_err());
+        assert_eq!(cursor.position(), 2);
+    }
+}
@@ -0,0 +1,175 @@
+//! Comparing packets and tracking the latest value of every tag.
+//!
+//! Encoders don't agree on whether unchanged values should be repeated in
+//! every packet. [`diff`] reports what actually changed between two packets,
+//! and [`PacketState`] carries values forward so that the latest packet can
+//! always be viewed as the complete current state.
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::klv::Klv;
+use crate::klv_packet::KlvPacket;
+use crate::klv_value::KlvValue;
+use crate::tag::Tag;
+
+/// A difference in one tag between two packets.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// The tag is only in the later packet.
+    Added(Klv),
+    /// The tag is only in the earlier packet.
+    Removed(Klv),
+    /// The tag is in both packets with different values.
+    Changed { before: Klv, after: Klv },
+}
+
+impl Change {
+    pub fn tag(&self) -> Tag {
+        match self {
+            Change::Added(field) | Change::Removed(field) => field.tag(),
+            Change::Changed { after, .. } => after.tag(),
+        }
+    }
+}
+
+/// Compare two packets, returning the changes in tag order.
+///
+/// The checksum is not compared as it changes with any other value.
+pub fn diff(before: &KlvPacket, after: &KlvPacket) -> Vec<Change> {
+    let fields = |packet: &KlvPacket| {
+        packet
+            .fields()
+            .filter(|field| field.tag() != Tag::Checksum)
+            .map(|field| (field.tag().id(), field.clone()))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let mut before = fields(before);
+    let after = fields(after);
+
+    let mut changes = Vec::new();
+    for (id, field) in after {
+        match before.remove(&id) {
+            None => changes.push(Change::Added(field)),
+            Some(old) if old != field => {
+                changes.push(Change::Changed { before: old, after: field })
+            }
+            Some(_) => {}
+        }
+    }
+    changes.extend(before.into_values().map(Change::Removed));
+    changes.sort_by_key(|change| change.tag().id());
+    changes
+}
+
+/// The latest value of every tag seen in a sequence of packets.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PacketState {
+    /// The latest value of each tag and the precision time stamp of the
+    /// packet it came from.
+    fields: BTreeMap<usize, (Option<u64>, Klv)>,
+}
+
+impl PacketState {
+    pub fn new() -> PacketState {
+        PacketState::default()
+    }
+
+    /// Apply the values in `packet`, returning the tags that were added or
+    /// changed. Tags missing from `packet` keep their previous value.
+    pub fn update(&mut self, packet: &KlvPacket) -> Vec<Change> {
+        let timestamp = match packet
+            .get_ref(Tag::PrecisionTimeStamp)
+            .map(|field| field.value())
+        {
+            Some(KlvValue::Uint64(timestamp)) => Some(*timestamp),
+            _ => None,
+        };
+
+        let mut changes = Vec::new();
+        for field in packet.fields() {
+            let previous = self
+                .fields
+                .insert(field.tag().id(), (timestamp, field.clone()));
+            if field.tag() == Tag::Checksum {
+                continue;
+            }
+            match previous {
+                None => changes.push(Change::Added(field.clone())),
+                Some((_, before)) if before != *field => changes
+                    .push(Change::Changed { before, after: field.clone() }),
+                Some(_) => {}
+            }
+        }
+        changes
+    }
+
+    pub fn get(&self, tag: Tag) -> Option<&Klv> {
+        self.fields.get(&tag.id()).map(|(_, field)| field)
+    }
+
+    /// Precision time stamp of the packet that last set `tag`.
+    pub fn updated_at(&self, tag: Tag) -> Option<u64> {
+        self.fields.get(&tag.id())?.0
+    }
+
+    /// The latest value of every tag, in tag order.
+    pub fn fields(&self) -> impl Iterator<Item = &Klv> {
+        self.fields.values().map(|(_, field)| field)
+    }
+
+    /// The complete current state as a packet.
+    pub fn to_packet(&self) -> KlvPacket {
+        KlvPacket::from_fields(self.fields().cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Change;
+    use super::PacketState;
+    use super::diff;
+    use crate::klv_packet::tests::packet_at;
+    use crate::tag::Tag;
+
+    #[test]
+    fn changes() {
+        // Mission ID of `A` and heading.
+        let before = packet_at(1, &[0x03, 0x01, b'A', 0x05, 0x02, 0x00, 0x01]);
+        // Mission ID of `B` and tail number.
+        let after = packet_at(1, &[0x03, 0x01, b'B', 0x04, 0x01, b'T']);
+
+        let changes = diff(&before, &after);
+        let tags = changes.iter().map(Change::tag).collect::<Vec<_>>();
+        assert_eq!(
+            tags,
+            [
+                Tag::MissionID,
+                Tag::PlatformTailNumber,
+                Tag::PlatformHeadingAngle
+            ]
+        );
+        assert!(matches!(changes[0], Change::Changed { .. }));
+        assert!(matches!(changes[1], Change::Added(_)));
+        assert!(matches!(changes[2], Change::Removed(_)));
+        assert!(diff(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn state() {
+        let mut state = PacketState::new();
+        let changes = state
+            .update(&packet_at(1, &[0x03, 0x01, b'A', 0x05, 0x02, 0x00, 0x01]));
+        assert_eq!(changes.len(), 3);
+
+        // Only the time stamp changes and the mission ID is carried forward.
+        let changes = state.update(&packet_at(2, &[0x05, 0x02, 0x00, 0x01]));
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].tag(), Tag::PrecisionTimeStamp);
+        assert_eq!(state.updated_at(Tag::MissionID), Some(1));
+        assert_eq!(state.updated_at(Tag::PlatformHeadingAngle), Some(2));
+
+        let packet = state.to_packet();
+        assert_eq!(packet.mission_id(), Some("A"));
+        assert_eq!(packet.precision_time_stamp(), Some(2));
+    }
+}
@@ -0,0 +1,231 @@
+//! Conversion of KLV packets into JSON, CSV and KML.
+//!
+//! Values are written as they are decoded, without converting them into
+//! engineering units. The exception is KML, where the sensor position is
+//! converted into degrees and metres as described in MISB ST 0601.19.
+//!
+//! Every writer takes a list of tags to include. An empty list includes every
+//! tag in the packet.
+use core::fmt::Result;
+use core::fmt::Write;
+
+use crate::klv_packet::KlvPacket;
+use crate::klv_value::KlvValue;
+use crate::tag::Tag;
+
+/// Write `packet` as a single line JSON object keyed by tag name.
+pub fn write_json(
+    out: &mut impl Write,
+    packet: &KlvPacket,
+    tags: &[Tag],
+) -> Result {
+    write!(out, "{{")?;
+    let fields = packet
+        .fields()
+        .filter(|field| tags.is_empty() || tags.contains(&field.tag()));
+    for (idx, field) in fields.enumerate() {
+        if idx > 0 {
+            write!(out, ",")?;
+        }
+        write!(out, "\"{}\":", field.tag().string())?;
+        write_json_value(out, field.value())?;
+    }
+    writeln!(out, "}}")
+}
+
+fn write_json_value(out: &mut impl Write, value: &KlvValue) -> Result {
+    match value {
+        KlvValue::Int(_)
+        | KlvValue::Int8(_)
+        | KlvValue::Int16(_)
+        | KlvValue::Int32(_)
+        | KlvValue::Uint(_)
+        | KlvValue::Uint8(_)
+        | KlvValue::Uint16(_)
+        | KlvValue::Uint32(_)
+        | KlvValue::Uint64(_) => write!(out, "{}", value),
+        KlvValue::IMAPB(float) if float.is_finite() => write!(out, "{}", float),
+        // Hex strings never need escaping.
+        KlvValue::Byte(_) | KlvValue::VLP(_) | KlvValue::DLP(_) => {
+            write!(out, "\"{}\"", value)
+        }
+        KlvValue::Utf8(text) => write_json_string(out, text),
+        KlvValue::FLP(sdcc) => {
+            write!(out, "{{")?;
+            for (idx, tag) in sdcc.tags().iter().enumerate() {
+                if idx > 0 {
+                    write!(out, ",")?;
+                }
+                write!(out, "\"{}\":", tag.string())?;
+                match sdcc.standard_deviation(*tag) {
+                    Some(sd) if sd.is_finite() => write!(out, "{}", sd)?,
+                    _ => write!(out, "null")?,
+                }
+            }
+            write!(out, "}}")
+        }
+        _ => write!(out, "null"),
+    }
+}
+
+fn write_json_string(out: &mut impl Write, text: &str) -> Result {
+    write!(out, "\"")?;
+    for c in text.chars() {
+        match c {
+            '"' => write!(out, "\\\"")?,
+            '\\' => write!(out, "\\\\")?,
+            '\n' => write!(out, "\\n")?,
+            '\r' => write!(out, "\\r")?,
+            '\t' => write!(out, "\\t")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => write!(out, "{}", c)?,
+        }
+    }
+    write!(out, "\"")
+}
+
+/// Write the CSV header row for the given columns.
+pub fn write_csv_header(out: &mut impl Write, tags: &[Tag]) -> Result {
+    for (idx, tag) in tags.iter().enumerate() {
+        if idx > 0 {
+            write!(out, ",")?;
+        }
+        write!(out, "{}", tag.string())?;
+    }
+    writeln!(out)
+}
+
+/// Write `packet` as a CSV row with a column for each of `tags`. Tags that
+/// are not in the packet are left empty.
+pub fn write_csv_row(
+    out: &mut impl Write,
+    packet: &KlvPacket,
+    tags: &[Tag],
+) -> Result {
+    for (idx, tag) in tags.iter().enumerate() {
+        if idx > 0 {
+            write!(out, ",")?;
+        }
+        let Some(field) = packet.get_ref(*tag) else {
+            continue;
+        };
+        match field.value() {
+            KlvValue::Utf8(text) => write_csv_string(out, text)?,
+            KlvValue::FLP(_) => {}
+            value => write!(out, "{}", value)?,
+        }
+    }
+    writeln!(out)
+}
+
+fn write_csv_string(out: &mut impl Write, text: &str) -> Result {
+    if !text.contains([',', '"', '\n', '\r']) {
+        return write!(out, "{}", text);
+    }
+    write!(out, "\"")?;
+    for c in text.chars() {
+        if c == '"' {
+            write!(out, "\"")?;
+        }
+        write!(out, "{}", c)?;
+    }
+    write!(out, "\"")
+}
+
+/// Sensor position of a packet as `(longitude, latitude, altitude)` in
+/// degrees and metres.
+fn sensor_position(packet: &KlvPacket) -> Option<(f64, f64, f64)> {
+    let (latitude, longitude) = packet.sensor_location()?;
+    let altitude =
+        packet.sensor_true_altitude().map_or(0.0, |altitude| altitude.0);
+
+    Some((longitude, latitude, altitude))
+}
+
+/// Write a KML document with the path of the sensor through `packets`.
+/// Packets without a sensor true altitude are placed at an altitude of zero.
+pub fn write_kml<'a>(
+    out: &mut impl Write,
+    name: &str,
+    packets: impl IntoIterator<Item = &'a KlvPacket>,
+) -> Result {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#)?;
+    writeln!(out, "<Document>")?;
+    writeln!(out, "<Placemark>")?;
+    write!(out, "<name>")?;
+    for c in name.chars() {
+        match c {
+            '<' => write!(out, "&lt;")?,
+            '>' => write!(out, "&gt;")?,
+            '&' => write!(out, "&amp;")?,
+            c => write!(out, "{}", c)?,
+        }
+    }
+    writeln!(out, "</name>")?;
+    writeln!(out, "<LineString>")?;
+    writeln!(out, "<altitudeMode>absolute</altitudeMode>")?;
+    writeln!(out, "<coordinates>")?;
+    for (longitude, latitude, altitude) in
+        packets.into_iter().filter_map(sensor_position)
+    {
+        writeln!(out, "{:.7},{:.7},{:.1}", longitude, latitude, altitude)?;
+    }
+    writeln!(out, "</coordinates>")?;
+    writeln!(out, "</LineString>")?;
+    writeln!(out, "</Placemark>")?;
+    writeln!(out, "</Document>")?;
+    writeln!(out, "</kml>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write_csv_header;
+    use super::write_csv_row;
+    use super::write_json;
+    use super::write_kml;
+    use crate::klv_packet::KlvPacket;
+    use crate::klv_packet::tests::packet_from_value;
+    use crate::tag::Tag;
+
+    fn packet() -> KlvPacket {
+        // Mission ID of `A"B`, sensor latitude of 45 degrees and sensor
+        // longitude of -90 degrees.
+        let bytes = packet_from_value(vec![
+            0x03, 0x03, b'A', b'"', b'B', 0x0D, 0x04, 0x3F, 0xFF, 0xFF, 0xFF,
+            0x0E, 0x04, 0xC0, 0x00, 0x00, 0x00,
+        ]);
+        KlvPacket::from_bytes(&bytes).unwrap().unwrap()
+    }
+
+    #[test]
+    fn json() {
+        let mut out = String::new();
+        write_json(&mut out, &packet(), &[Tag::MissionID, Tag::SensorLatitude])
+            .unwrap();
+        assert_eq!(
+            out,
+            "{\"MissionID\":\"A\\\"B\",\"SensorLatitude\":1073741823}\n"
+        );
+    }
+
+    #[test]
+    fn csv() {
+        let tags =
+            [Tag::PrecisionTimeStamp, Tag::MissionID, Tag::PlatformTailNumber];
+        let mut out = String::new();
+        write_csv_header(&mut out, &tags).unwrap();
+        write_csv_row(&mut out, &packet(), &tags).unwrap();
+        assert_eq!(
+            out,
+            "PrecisionTimeStamp,MissionID,PlatformTailNumber\n4822678189205111,\"A\"\"B\",\n"
+        );
+    }
+
+    #[test]
+    fn kml() {
+        let mut out = String::new();
+        write_kml(&mut out, "Flight", [&packet()]).unwrap();
+        assert!(out.contains("-90.0000000,45.0000000,0.0\n"));
+    }
+}
@@ -0,0 +1,253 @@
+//! Information from this page was gathered from the tag descriptions in MISB
+//! ST 0601.19.
+//!
+//! Typed views over the UAS LS tags that hold bit-flags or enumerated values
+//! rather than measurements.
+use alloc::boxed::Box;
+
+use strum_macros::FromRepr;
+
+/// Generic Flag Data (tag 47).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GenericFlags(u8);
+
+impl GenericFlags {
+    pub fn new(bits: u8) -> GenericFlags {
+        GenericFlags(bits)
+    }
+
+    /// The raw flag byte.
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    fn bit(self, idx: u8) -> bool {
+        self.0 & (1 << idx) != 0
+    }
+
+    /// The laser range finder is on and lasing.
+    pub fn laser_on(self) -> bool {
+        self.bit(0)
+    }
+
+    /// The sensor is automatically tracking a target.
+    pub fn auto_track(self) -> bool {
+        self.bit(1)
+    }
+
+    /// Polarity of the infrared sensor.
+    pub fn ir_polarity(self) -> IrPolarity {
+        if self.bit(2) { IrPolarity::BlackHot } else { IrPolarity::WhiteHot }
+    }
+
+    /// Icing has been detected.
+    pub fn icing_detected(self) -> bool {
+        self.bit(3)
+    }
+
+    /// How the slant range was determined.
+    pub fn slant_range_source(self) -> SlantRangeSource {
+        if self.bit(4) {
+            SlantRangeSource::Measured
+        } else {
+            SlantRangeSource::Calculated
+        }
+    }
+
+    /// The image is invalid.
+    pub fn image_invalid(self) -> bool {
+        self.bit(5)
+    }
+}
+
+/// Infrared sensor polarity from [`GenericFlags`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IrPolarity {
+    WhiteHot,
+    BlackHot,
+}
+
+/// Source of the slant range from [`GenericFlags`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlantRangeSource {
+    Calculated,
+    Measured,
+}
+
+/// Icing Detected (tag 34).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, FromRepr)]
+#[repr(u8)]
+pub enum IcingDetected {
+    DetectorOff = 0,
+    NoIcingDetected = 1,
+    IcingDetected = 2,
+}
+
+/// Sensor Field of View Name (tag 63).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, FromRepr)]
+#[repr(u8)]
+pub enum SensorFieldOfViewName {
+    Ultranarrow = 0,
+    Narrow = 1,
+    Medium = 2,
+    Wide = 3,
+    Ultrawide = 4,
+    NarrowMedium = 5,
+    TwoTimesUltranarrow = 6,
+    FourTimesUltranarrow = 7,
+    ContinuousZoom = 8,
+}
+
+/// Operational Mode (tag 77).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, FromRepr)]
+#[repr(u8)]
+pub enum OperationalMode {
+    Other = 0,
+    Operational = 1,
+    Training = 2,
+    Exercise = 3,
+    Maintenance = 4,
+    Test = 5,
+}
+
+/// Platform Status (tag 125).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, FromRepr)]
+#[repr(u8)]
+pub enum PlatformStatus {
+    Active = 0,
+    PreFlight = 1,
+    PreFlightTaxiing = 2,
+    RunUp = 3,
+    TakeOff = 4,
+    Ingress = 5,
+    ManualOperation = 6,
+    AutomatedOrbit = 7,
+    Transitioning = 8,
+    Egress = 9,
+    Landing = 10,
+    LandedTaxiing = 11,
+    LandedParked = 12,
+}
+
+/// Sensor Control Mode (tag 126).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, FromRepr)]
+#[repr(u8)]
+pub enum SensorControlMode {
+    Off = 0,
+    HomePosition = 1,
+    Uncontrolled = 2,
+    ManualControl = 3,
+    Calibrating = 4,
+    AutoHoldingPosition = 5,
+    AutoTracking = 6,
+}
+
+/// A single navigation source from [`PositionMethodSource`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, FromRepr)]
+#[repr(u8)]
+pub enum PositionSource {
+    OnboardIns = 0,
+    Gps = 1,
+    Galileo = 2,
+    Qzss = 3,
+    Navic = 4,
+    Glonass = 5,
+    BeiDou1 = 6,
+    BeiDou2 = 7,
+}
+
+/// Position Method Source (tag 124).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PositionMethodSource(u64);
+
+impl PositionMethodSource {
+    pub fn new(bits: u64) -> PositionMethodSource {
+        PositionMethodSource(bits)
+    }
+
+    /// The raw flag bits.
+    pub fn bits(self) -> u64 {
+        self.0
+    }
+
+    /// The position was determined using `source`.
+    pub fn contains(self, source: PositionSource) -> bool {
+        self.0 & (1 << source as u8) != 0
+    }
+
+    /// All sources used to determine the position.
+    pub fn sources(self) -> impl Iterator<Item = PositionSource> {
+        (0..u8::BITS as u8)
+            .filter_map(PositionSource::from_repr)
+            .filter(move |source| self.contains(*source))
+    }
+}
+
+/// Active Payloads (tag 139).
+///
+/// Each bit represents the payload with the same ID from the Payload List,
+/// starting from the least significant bit of the last byte.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActivePayloads(Box<[u8]>);
+
+impl ActivePayloads {
+    pub fn new(bytes: &[u8]) -> ActivePayloads {
+        ActivePayloads(bytes.into())
+    }
+
+    /// The payload with ID `id` is active.
+    pub fn is_active(&self, id: usize) -> bool {
+        let Some(byte) =
+            self.0.len().checked_sub(id / 8 + 1).map(|idx| self.0[idx])
+        else {
+            return false;
+        };
+        byte & (1 << (id % 8)) != 0
+    }
+
+    /// IDs of all active payloads.
+    pub fn ids(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.0.len() * 8).filter(|id| self.is_active(*id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ActivePayloads;
+    use super::GenericFlags;
+    use super::IrPolarity;
+    use super::PositionMethodSource;
+    use super::PositionSource;
+    use super::SlantRangeSource;
+
+    #[test]
+    fn generic_flags() {
+        let flags = GenericFlags::new(0b0011_0101);
+        assert!(flags.laser_on());
+        assert!(!flags.auto_track());
+        assert_eq!(flags.ir_polarity(), IrPolarity::BlackHot);
+        assert!(!flags.icing_detected());
+        assert_eq!(flags.slant_range_source(), SlantRangeSource::Measured);
+        assert!(flags.image_invalid());
+    }
+
+    #[test]
+    fn position_method_source() {
+        let source = PositionMethodSource::new(0b0010_0011);
+        assert_eq!(
+            source.sources().collect::<Vec<_>>(),
+            vec![
+                PositionSource::OnboardIns,
+                PositionSource::Gps,
+                PositionSource::Glonass
+            ]
+        );
+    }
+
+    #[test]
+    fn active_payloads() {
+        let payloads = ActivePayloads::new(&[0b0000_0001, 0b0000_0101]);
+        assert_eq!(payloads.ids().collect::<Vec<_>>(), vec![0, 2, 8]);
+        assert!(!payloads.is_active(30));
+    }
+}
@@ -0,0 +1,727 @@
+//! Synthetic MISB ST 0601 packets for testing and simulation.
+//!
+//! A [`Generator`] flies a simulated platform around a [`FlightPattern`] and
+//! produces a packet for each step of the flight. The sensor either stares at
+//! a point on the ground or looks out of the side of the platform, slewing
+//! back and forth if asked to, and the frame center, slant range and corners
+//! of the footprint are calculated from where it is pointing.
+//!
+//! The ground is treated as flat and level at an elevation of zero, and the
+//! sensor angles are calculated as if the platform were level, so the data
+//! is consistent with itself rather than survey accurate. [`Faults`] can
+//! corrupt the packets to test how a receiver copes with bad data.
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+
+use crate::ErrorKind;
+use crate::klv_packet::UAS_CHECKSUM;
+use crate::klv_packet::UAS_LENGTH_ENCODING;
+use crate::klv_packet::UAS_LOCAL_SET_UNIVERSAL_LABEL;
+use crate::klv_packet::UAS_TAG_ENCODING;
+use crate::tag::Tag;
+use crate::units;
+
+/// Length of one degree of latitude, in metres.
+const METERS_PER_DEGREE: f64 = 111_320.0;
+/// Acceleration due to gravity, in metres per second squared.
+const GRAVITY: f64 = 9.806_65;
+/// Largest corner offset that can be encoded, in degrees.
+const MAX_CORNER_OFFSET: f64 = 0.075;
+/// Version of MISB ST 0601 the packets claim to follow.
+const UAS_LS_VERSION: f64 = 19.0;
+
+/// The path flown by the platform. Turns are always to the right.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FlightPattern {
+    /// Circles of `radius` metres around a point.
+    Orbit { latitude: f64, longitude: f64, radius: f64 },
+    /// A racetrack centred on a point, with straight legs of `length` metres
+    /// along `heading` degrees joined by half circles of `radius` metres.
+    Racetrack {
+        latitude: f64,
+        longitude: f64,
+        length: f64,
+        radius: f64,
+        heading: f64,
+    },
+    /// A straight line from a point along `heading` degrees.
+    Line { latitude: f64, longitude: f64, heading: f64 },
+}
+
+/// Where the sensor points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pointing {
+    /// Stare at a point on the ground.
+    Target { latitude: f64, longitude: f64 },
+    /// Look out of the right side of the platform, `depression` degrees below
+    /// the horizon.
+    Broadside { depression: f64 },
+}
+
+/// The imaging sensor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sensor {
+    pub pointing: Pointing,
+    /// Horizontal field of view, in degrees.
+    pub horizontal_fov: f64,
+    /// Vertical field of view, in degrees.
+    pub vertical_fov: f64,
+    /// How far the sensor slews either side of where it points, in degrees.
+    pub slew: f64,
+    /// Time taken to slew from one side to the other and back, in seconds.
+    pub slew_period: f64,
+}
+
+impl Default for Sensor {
+    fn default() -> Self {
+        Sensor {
+            pointing: Pointing::Broadside { depression: 45.0 },
+            horizontal_fov: 10.0,
+            vertical_fov: 7.5,
+            slew: 0.0,
+            slew_period: 30.0,
+        }
+    }
+}
+
+/// How often each kind of fault is injected, as a probability from zero to
+/// one.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Faults {
+    /// The checksum of the packet is wrong.
+    pub bad_checksum: f64,
+    /// The packet is cut short.
+    pub truncate: f64,
+    /// Each tag other than the precision time stamp, version number and
+    /// checksum is left out.
+    pub drop_tag: f64,
+}
+
+/// Everything that describes a simulated flight.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scenario {
+    pub pattern: FlightPattern,
+    /// Altitude of the platform above mean sea level, in metres.
+    pub altitude: f64,
+    /// Ground speed of the platform, in metres per second. Must be finite and
+    /// not negative.
+    pub speed: f64,
+    /// Packets generated per second of flight. Must be finite and more than
+    /// zero.
+    pub rate: f64,
+    /// Precision time stamp of the first packet, in microseconds since the
+    /// UNIX epoch.
+    pub start: u64,
+    pub mission_id: Arc<str>,
+    pub sensor: Sensor,
+    pub faults: Faults,
+    /// Seed for the faults, so a run can be repeated.
+    pub seed: u64,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Scenario {
+            pattern: FlightPattern::Orbit {
+                latitude: 0.0,
+                longitude: 0.0,
+                radius: 2000.0,
+            },
+            altitude: 1500.0,
+            speed: 40.0,
+            rate: 30.0,
+            start: 1_700_000_000_000_000,
+            mission_id: "SYNTHETIC".into(),
+            sensor: Sensor::default(),
+            faults: Faults::default(),
+            seed: 0x5EED,
+        }
+    }
+}
+
+/// A generated packet along with the faults that were injected into it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntheticPacket {
+    pub bytes: Vec<u8>,
+    /// Precision time stamp of the packet.
+    pub timestamp: u64,
+    /// Tags that were left out.
+    pub dropped: Vec<Tag>,
+    pub bad_checksum: bool,
+    pub truncated: bool,
+}
+
+/// Position and attitude of the platform relative to the reference point of
+/// the flight pattern.
+struct Pose {
+    north: f64,
+    east: f64,
+    /// Heading in degrees.
+    heading: f64,
+    /// Roll in degrees, positive with the right wing down.
+    roll: f64,
+}
+
+/// Produces an endless sequence of packets for a [`Scenario`].
+#[derive(Clone, Debug)]
+pub struct Generator {
+    scenario: Scenario,
+    /// Number of packets generated so far.
+    count: u64,
+    rng: Rng,
+}
+
+impl Generator {
+    /// Create a generator for `scenario`.
+    ///
+    /// Fails with [`ErrorKind::InvalidScenario`] if the rate or speed of the
+    /// scenario is out of range.
+    pub fn new(scenario: Scenario) -> Result<Generator, ErrorKind> {
+        let invalid = |reason: &str| ErrorKind::InvalidScenario(reason.into());
+        if !(scenario.rate.is_finite() && scenario.rate > 0.0) {
+            return Err(invalid("the rate must be more than zero"));
+        }
+        if !(scenario.speed.is_finite() && scenario.speed >= 0.0) {
+            return Err(invalid("the speed can't be negative"));
+        }
+
+        let rng = Rng::new(scenario.seed);
+        Ok(Generator { scenario, count: 0, rng })
+    }
+
+    pub fn scenario(&self) -> &Scenario {
+        &self.scenario
+    }
+
+    /// Values of every tag in the packet for `seconds` into the flight.
+    fn fields(&self, seconds: f64) -> Vec<(Tag, f64)> {
+        let scenario = &self.scenario;
+        let sensor = &scenario.sensor;
+        let (latitude, longitude) = scenario.pattern.reference();
+        let pose = scenario.pattern.pose(scenario.speed, seconds);
+        // Metres east of the reference point per degree of longitude.
+        let scale = METERS_PER_DEGREE * libm::cos(latitude.to_radians());
+        let to_degrees = |north: f64, east: f64| {
+            (latitude + north / METERS_PER_DEGREE, longitude + east / scale)
+        };
+        let (sensor_latitude, sensor_longitude) =
+            to_degrees(pose.north, pose.east);
+
+        let (azimuth, depression) = match sensor.pointing {
+            Pointing::Target {
+                latitude: target_lat,
+                longitude: target_lon,
+            } => {
+                let north =
+                    (target_lat - latitude) * METERS_PER_DEGREE - pose.north;
+                let east = (target_lon - longitude) * scale - pose.east;
+                let range = libm::sqrt(north * north + east * east);
+                (
+                    libm::atan2(east, north).to_degrees(),
+                    libm::atan2(scenario.altitude, range).to_degrees(),
+                )
+            }
+            Pointing::Broadside { depression } => {
+                (pose.heading + 90.0, depression)
+            }
+        };
+        let slew = if sensor.slew_period > 0.0 {
+            sensor.slew * libm::sin(2.0 * PI * seconds / sensor.slew_period)
+        } else {
+            0.0
+        };
+        let azimuth = azimuth + slew;
+
+        let mut fields = vec![
+            (Tag::PlatformHeadingAngle, wrap(pose.heading)),
+            (Tag::PlatformPitchAngle, 0.0),
+            (Tag::PlatformRollAngle, pose.roll),
+            (Tag::PlatformTrueAirspeed, scenario.speed),
+            (Tag::SensorLatitude, sensor_latitude),
+            (Tag::SensorLongitude, sensor_longitude),
+            (Tag::SensorTrueAltitude, scenario.altitude),
+            (Tag::SensorHorizontalFieldOfView, sensor.horizontal_fov),
+            (Tag::SensorVerticalFieldOfView, sensor.vertical_fov),
+            (Tag::SensorRelativeAzimuthAngle, wrap(azimuth - pose.heading)),
+            (Tag::SensorRelativeElevationAngle, -depression),
+            (Tag::SensorRelativeRollAngle, 0.0),
+        ];
+
+        // Where a ray from the sensor hits the ground, in metres north and
+        // east of the reference point.
+        let ground = |azimuth: f64, depression: f64| {
+            (depression > 0.0).then(|| {
+                let range =
+                    scenario.altitude / libm::tan(depression.to_radians());
+                (
+                    pose.north + range * libm::cos(azimuth.to_radians()),
+                    pose.east + range * libm::sin(azimuth.to_radians()),
+                )
+            })
+        };
+        let Some((north, east)) = ground(azimuth, depression) else {
+            fields.push((Tag::PlatformGroundSpeed, scenario.speed));
+            return fields;
+        };
+        let slant_range =
+            scenario.altitude / libm::sin(depression.to_radians());
+        let (center_latitude, center_longitude) = to_degrees(north, east);
+        fields.extend([
+            (Tag::SlantRange, slant_range),
+            (
+                Tag::TargetWidth,
+                2.0 * slant_range
+                    * libm::tan((sensor.horizontal_fov / 2.0).to_radians()),
+            ),
+            (Tag::FrameCenterLatitude, center_latitude),
+            (Tag::FrameCenterLongitude, center_longitude),
+            (Tag::FrameCenterElevation, 0.0),
+        ]);
+
+        // Upper left, upper right, lower right and lower left. The upper edge
+        // of the frame is the one furthest from the sensor.
+        let (half_width, half_height) =
+            (sensor.horizontal_fov / 2.0, sensor.vertical_fov / 2.0);
+        let corners = [
+            (-half_width, -half_height),
+            (half_width, -half_height),
+            (half_width, half_height),
+            (-half_width, half_height),
+        ]
+        .map(|(across, down)| {
+            let (north, east) = ground(azimuth + across, depression + down)?;
+            let (latitude, longitude) = to_degrees(north, east);
+            let offset =
+                (latitude - center_latitude, longitude - center_longitude);
+            (libm::fabs(offset.0) <= MAX_CORNER_OFFSET
+                && libm::fabs(offset.1) <= MAX_CORNER_OFFSET)
+                .then_some(offset)
+        });
+        if corners.iter().all(Option::is_some) {
+            let tags = [
+                Tag::OffsetCornerLatitudePoint1,
+                Tag::OffsetCornerLongitudePoint1,
+                Tag::OffsetCornerLatitudePoint2,
+                Tag::OffsetCornerLongitudePoint2,
+                Tag::OffsetCornerLatitudePoint3,
+                Tag::OffsetCornerLongitudePoint3,
+                Tag::OffsetCornerLatitudePoint4,
+                Tag::OffsetCornerLongitudePoint4,
+            ];
+            let offsets =
+                corners.iter().flatten().flat_map(|(lat, lon)| [*lat, *lon]);
+            fields.extend(tags.into_iter().zip(offsets));
+        }
+
+        fields.push((Tag::PlatformGroundSpeed, scenario.speed));
+        fields
+    }
+
+    /// Generate the next packet of the flight.
+    pub fn packet(&mut self) -> SyntheticPacket {
+        let scenario = &self.scenario;
+        let seconds = self.count as f64 / scenario.rate;
+        // Time stamps stop at the end of time rather than overflowing.
+        let timestamp = scenario
+            .start
+            .saturating_add(libm::round(seconds * 1_000_000.0) as u64);
+        self.count += 1;
+
+        let mut body = Vec::new();
+        field(&mut body, Tag::PrecisionTimeStamp, &timestamp.to_be_bytes());
+        let mut dropped = Vec::new();
+        if self.rng.chance(scenario.faults.drop_tag) {
+            dropped.push(Tag::MissionID);
+        } else {
+            field(&mut body, Tag::MissionID, scenario.mission_id.as_bytes());
+        }
+        for (tag, value) in self.fields(seconds) {
+            if self.rng.chance(self.scenario.faults.drop_tag) {
+                dropped.push(tag);
+            } else if let Some(raw) = units::unmap(tag, value) {
+                field(&mut body, tag, &raw);
+            }
+        }
+        let version =
+            units::unmap(Tag::UasDatalinkLsVersionNumber, UAS_LS_VERSION);
+        field(
+            &mut body,
+            Tag::UasDatalinkLsVersionNumber,
+            &version.unwrap_or_default(),
+        );
+
+        // The checksum covers its own tag and length.
+        let checksum_tag = Tag::Checksum.id() as u8;
+        body.extend_from_slice(&[checksum_tag, 2]);
+        let mut bytes = Vec::from(UAS_LOCAL_SET_UNIVERSAL_LABEL);
+        let _ = UAS_LENGTH_ENCODING.write(body.len() + 2, &mut bytes);
+        bytes.extend_from_slice(&body);
+        let mut checksum = UAS_CHECKSUM.calculate(&bytes).unwrap_or_default();
+
+        let faults = self.scenario.faults;
+        let bad_checksum = self.rng.chance(faults.bad_checksum);
+        if bad_checksum {
+            checksum ^= 1 + self.rng.below(u16::MAX.into()) as u16;
+        }
+        bytes.extend_from_slice(&checksum.to_be_bytes());
+
+        let truncated = self.rng.chance(faults.truncate);
+        if truncated {
+            let length = 1 + self.rng.below(bytes.len() as u64 - 1) as usize;
+            bytes.truncate(length);
+        }
+
+        SyntheticPacket { bytes, timestamp, dropped, bad_checksum, truncated }
+    }
+}
+
+impl Iterator for Generator {
+    type Item = SyntheticPacket;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.packet())
+    }
+}
+
+impl FlightPattern {
+    /// The point the pattern is flown around or starts from.
+    fn reference(&self) -> (f64, f64) {
+        match *self {
+            FlightPattern::Orbit { latitude, longitude, .. }
+            | FlightPattern::Racetrack { latitude, longitude, .. }
+            | FlightPattern::Line { latitude, longitude, .. } => {
+                (latitude, longitude)
+            }
+        }
+    }
+
+    /// Where the platform is after `seconds` at `speed` metres per second.
+    fn pose(&self, speed: f64, seconds: f64) -> Pose {
+        let distance = speed * seconds;
+        let bank = |radius: f64| {
+            libm::atan(speed * speed / (GRAVITY * radius)).to_degrees()
+        };
+
+        match *self {
+            FlightPattern::Orbit { radius, .. } => {
+                // Angle around the circle, clockwise from north.
+                let angle = distance / radius;
+                Pose {
+                    north: radius * libm::cos(angle),
+                    east: radius * libm::sin(angle),
+                    heading: angle.to_degrees() + 90.0,
+                    roll: bank(radius),
+                }
+            }
+            FlightPattern::Racetrack { length, radius, heading, .. } => {
+                let turn = PI * radius;
+                let distance = distance % (2.0 * (length + turn));
+                // Position along and to the right of the axis of the track,
+                // heading relative to the axis and roll.
+                let (along, right, relative, roll) = if distance < length {
+                    (distance - length / 2.0, -radius, 0.0, 0.0)
+                } else if distance < length + turn {
+                    let angle = (distance - length) / radius;
+                    (
+                        length / 2.0 + radius * libm::sin(angle),
+                        -radius * libm::cos(angle),
+                        angle.to_degrees(),
+                        bank(radius),
+                    )
+                } else if distance < 2.0 * length + turn {
+                    (
+                        length / 2.0 - (distance - length - turn),
+                        radius,
+                        180.0,
+                        0.0,
+                    )
+                } else {
+                    let angle = (distance - 2.0 * length - turn) / radius;
+                    (
+                        -length / 2.0 - radius * libm::sin(angle),
+                        radius * libm::cos(angle),
+                        180.0 + angle.to_degrees(),
+                        bank(radius),
+                    )
+                };
+                let (sin, cos) = libm::sincos(heading.to_radians());
+                Pose {
+                    north: along * cos - right * sin,
+                    east: along * sin + right * cos,
+                    heading: heading + relative,
+                    roll,
+                }
+            }
+            FlightPattern::Line { heading, .. } => {
+                let (sin, cos) = libm::sincos(heading.to_radians());
+                Pose {
+                    north: distance * cos,
+                    east: distance * sin,
+                    heading,
+                    roll: 0.0,
+                }
+            }
+        }
+    }
+}
+
+/// Append a field with a BER-OID tag and BER length to `body`.
+fn field(body: &mut Vec<u8>, tag: Tag, value: &[u8]) {
+    let _ = UAS_TAG_ENCODING.write(tag.id(), body);
+    let _ = UAS_LENGTH_ENCODING.write(value.len(), body);
+    body.extend_from_slice(value);
+}
+
+/// Wrap an angle in degrees into the range 0 to 360.
+fn wrap(degrees: f64) -> f64 {
+    let degrees = libm::fmod(degrees, 360.0);
+    if degrees < 0.0 { degrees + 360.0 } else { degrees }
+}
+
+/// A small xorshift generator, so faults can be repeated from a seed without
+/// depending on a random number crate.
+#[derive(Clone, Debug)]
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        // Zero would produce nothing but zeros.
+        Rng(seed.max(1))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number from zero up to, but not including, `bound`.
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound.max(1)
+    }
+
+    /// Returns `true` with the given probability.
+    fn chance(&mut self, probability: f64) -> bool {
+        // The top 53 bits make a uniform float from zero to one.
+        let sample = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        sample < probability
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::Faults;
+    use super::FlightPattern;
+    use super::Generator;
+    use super::Pointing;
+    use super::Scenario;
+    use super::Sensor;
+    use crate::ErrorKind;
+    use crate::klv_packet::KlvPacket;
+    use crate::local_set::ChecksumPolicy;
+    use crate::tag::Tag;
+
+    fn orbit() -> Scenario {
+        Scenario {
+            pattern: FlightPattern::Orbit {
+                latitude: 35.0,
+                longitude: -117.0,
+                radius: 3000.0,
+            },
+            sensor: Sensor {
+                pointing: Pointing::Target {
+                    latitude: 35.0,
+                    longitude: -117.0,
+                },
+                ..Sensor::default()
+            },
+            ..Scenario::default()
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> KlvPacket {
+        KlvPacket::from_bytes(bytes).unwrap().unwrap()
+    }
+
+    #[test]
+    fn orbit_stares_at_target() {
+        let mut previous = None;
+        for synthetic in Generator::new(orbit()).unwrap().step_by(97).take(40) {
+            let packet = decode(&synthetic.bytes);
+            assert_eq!(
+                packet.precision_time_stamp(),
+                Some(synthetic.timestamp)
+            );
+            assert_eq!(packet.mission_id(), Some("SYNTHETIC"));
+
+            let (latitude, longitude) = packet.frame_center().unwrap();
+            assert!((latitude - 35.0).abs() < 1e-4, "{}", latitude);
+            assert!((longitude + 117.0).abs() < 1e-4, "{}", longitude);
+            let slant_range = packet.slant_range().unwrap().0;
+            assert!((slant_range - 3000f64.hypot(1500.0)).abs() < 1.0);
+            // The target is off the right wing.
+            let azimuth = packet.sensor_relative_azimuth_angle().unwrap().0;
+            assert!((azimuth - 90.0).abs() < 0.1, "{}", azimuth);
+            assert!(packet.offset_corner_latitude_point_1().is_some());
+
+            let location = packet.sensor_location().unwrap();
+            assert_ne!(previous, Some(location));
+            previous = Some(location);
+        }
+    }
+
+    #[test]
+    fn footprint() {
+        let scenario = Scenario {
+            pattern: FlightPattern::Line {
+                latitude: 0.0,
+                longitude: 0.0,
+                heading: 0.0,
+            },
+            sensor: Sensor { slew: 20.0, ..Sensor::default() },
+            ..Scenario::default()
+        };
+        let generator = Generator::new(scenario).unwrap();
+        for synthetic in generator.step_by(50).take(20) {
+            let packet = decode(&synthetic.bytes);
+            let (sensor_lat, sensor_lon) = packet.sensor_location().unwrap();
+            let (center_lat, center_lon) = packet.frame_center().unwrap();
+            // Looking 45 degrees down, the frame center is as far away as
+            // the platform is high.
+            let ground = (center_lat - sensor_lat)
+                .hypot(center_lon - sensor_lon)
+                * super::METERS_PER_DEGREE;
+            assert!((ground - 1500.0).abs() < 1.0, "{}", ground);
+            // The upper corners are further away than the lower ones.
+            let upper = packet.offset_corner_longitude_point_1().unwrap().0;
+            let lower = packet.offset_corner_longitude_point_4().unwrap().0;
+            assert!(upper > lower);
+        }
+    }
+
+    #[test]
+    fn racetrack_is_continuous() {
+        let scenario = Scenario {
+            pattern: FlightPattern::Racetrack {
+                latitude: 10.0,
+                longitude: 20.0,
+                length: 4000.0,
+                radius: 1000.0,
+                heading: 30.0,
+            },
+            rate: 1.0,
+            ..Scenario::default()
+        };
+        let step = scenario.speed;
+        let locations: Vec<_> = Generator::new(scenario)
+            .unwrap()
+            .take(500)
+            .map(|synthetic| {
+                decode(&synthetic.bytes).sensor_location().unwrap()
+            })
+            .collect();
+        for pair in locations.windows(2) {
+            let moved = (pair[1].0 - pair[0].0).hypot(pair[1].1 - pair[0].1)
+                * super::METERS_PER_DEGREE;
+            assert!(moved < step * 1.1, "{}", moved);
+        }
+    }
+
+    #[test]
+    fn rate() {
+        let scenario = Scenario { rate: 4.0, start: 1000, ..orbit() };
+        let timestamps: Vec<_> = Generator::new(scenario)
+            .unwrap()
+            .take(3)
+            .map(|p| p.timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![1000, 251_000, 501_000]);
+    }
+
+    #[test]
+    fn faults() {
+        let faults = |faults| Scenario { faults, ..orbit() };
+
+        let bad = Faults { bad_checksum: 1.0, ..Faults::default() };
+        for synthetic in Generator::new(faults(bad)).unwrap().take(10) {
+            assert!(synthetic.bad_checksum);
+            assert!(matches!(
+                KlvPacket::from_bytes(&synthetic.bytes),
+                Err(ErrorKind::InvalidChecksum)
+            ));
+            let lenient = KlvPacket::from_bytes_with_checksum(
+                &synthetic.bytes,
+                ChecksumPolicy::Lenient,
+            );
+            assert!(lenient.unwrap().is_some());
+        }
+
+        let truncate = Faults { truncate: 1.0, ..Faults::default() };
+        for synthetic in Generator::new(faults(truncate)).unwrap().take(10) {
+            assert!(synthetic.truncated);
+            assert!(!matches!(
+                KlvPacket::from_bytes(&synthetic.bytes),
+                Ok(Some(_))
+            ));
+        }
+
+        let drop = Faults { drop_tag: 1.0, ..Faults::default() };
+        let synthetic = Generator::new(faults(drop)).unwrap().next().unwrap();
+        let tags: Vec<_> = decode(&synthetic.bytes)
+            .fields()
+            .map(|field| field.tag())
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                Tag::PrecisionTimeStamp,
+                Tag::UasDatalinkLsVersionNumber,
+                Tag::Checksum
+            ]
+        );
+        assert!(synthetic.dropped.contains(&Tag::SensorLatitude));
+    }
+
+    #[test_case(Scenario { rate: 0.0, ..orbit() }; "Zero rate")]
+    #[test_case(Scenario { rate: f64::NAN, ..orbit() }; "NaN rate")]
+    #[test_case(Scenario { rate: f64::INFINITY, ..orbit() }; "Infinite rate")]
+    #[test_case(Scenario { speed: -1.0, ..orbit() }; "Negative speed")]
+    #[test_case(Scenario { speed: f64::INFINITY, ..orbit() }; "Infinite speed")]
+    fn invalid_scenario(scenario: Scenario) {
+        assert!(matches!(
+            Generator::new(scenario),
+            Err(ErrorKind::InvalidScenario(_))
+        ));
+    }
+
+    #[test]
+    fn end_of_time() {
+        let scenario = Scenario { start: u64::MAX - 10, ..orbit() };
+        let timestamps: Vec<_> = Generator::new(scenario)
+            .unwrap()
+            .take(3)
+            .map(|p| p.timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![u64::MAX - 10, u64::MAX, u64::MAX]);
+    }
+
+    #[test]
+    fn repeatable() {
+        let scenario = Scenario {
+            faults: Faults { bad_checksum: 0.3, truncate: 0.3, drop_tag: 0.1 },
+            ..orbit()
+        };
+        let first: Vec<_> =
+            Generator::new(scenario.clone()).unwrap().take(50).collect();
+        let second: Vec<_> =
+            Generator::new(scenario).unwrap().take(50).collect();
+        assert_eq!(first, second);
+        assert!(first.iter().any(|p| p.truncated));
+        assert!(first.iter().any(|p| !p.truncated && !p.bad_checksum));
+    }
+}
@@ -0,0 +1,115 @@
+//! Information from this page was gathered from MISB ST 1201.4, "Floating
+//! Point to Integer Mapping".
+//!
+//! IMAPB maps a floating point value within a known range onto an unsigned
+//! integer of a fixed number of bytes. The most significant bit of the
+//! integer is reserved for special values (infinities and NaNs).
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Get the scaling factors used by IMAPB for the given range and length.
+///
+/// Returns `(forward scale, reverse scale, zero offset)`.
+fn parameters(min: f64, max: f64, length: usize) -> (f64, f64, f64) {
+    let b_pow = libm::ceil(libm::log2(max - min));
+    let d_pow = (8 * length - 1) as f64;
+    let forward = libm::pow(2.0, d_pow - b_pow);
+    let reverse = libm::pow(2.0, b_pow - d_pow);
+    let zero_offset = if min < 0.0 && max > 0.0 {
+        forward * min - libm::floor(forward * min)
+    } else {
+        0.0
+    };
+
+    (forward, reverse, zero_offset)
+}
+
+/// Decode the IMAPB encoded `bytes` into a floating point value within the
+/// range `min..=max`.
+///
+/// Returns `None` if `bytes` is empty or longer than 8 bytes.
+pub fn imapb(min: f64, max: f64, bytes: &[u8]) -> Option<f64> {
+    if bytes.is_empty() || bytes.len() > 8 {
+        return None;
+    }
+
+    // Special values are flagged using the most significant bit.
+    if bytes[0] & 0x80 != 0 {
+        return Some(match bytes[0] & 0xF0 {
+            0xC0 => f64::INFINITY,
+            0xE0 => f64::NEG_INFINITY,
+            _ => f64::NAN,
+        });
+    }
+
+    let value =
+        bytes.iter().fold(0u64, |value, byte| (value << 8) | *byte as u64);
+    let (_, reverse, zero_offset) = parameters(min, max, bytes.len());
+
+    Some(reverse * (value as f64 - zero_offset) + min)
+}
+
+/// Encode `value` into `length` bytes using IMAPB for the range `min..=max`.
+///
+/// Values outside of the range are clamped to it. Infinities and NaN are
+/// encoded as their special values.
+pub fn to_imapb(min: f64, max: f64, length: usize, value: f64) -> Vec<u8> {
+    let mut bytes = vec![0; length];
+    if length == 0 {
+        return bytes;
+    }
+
+    if value.is_nan() {
+        bytes[0] = 0xD0;
+        return bytes;
+    }
+    if value == f64::INFINITY {
+        bytes[0] = 0xC0;
+        return bytes;
+    }
+    if value == f64::NEG_INFINITY {
+        bytes[0] = 0xE0;
+        return bytes;
+    }
+
+    let (forward, _, zero_offset) = parameters(min, max, length);
+    let value = value.clamp(min, max);
+    let mut encoded = (forward * (value - min) + zero_offset) as u64;
+    for byte in bytes.iter_mut().rev() {
+        *byte = encoded as u8;
+        encoded >>= 8;
+    }
+
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::imapb;
+    use super::to_imapb;
+
+    #[test_case(0.0, 1_500_000.0, 3, 1000.0; "Positive range")]
+    #[test_case(-900.0, 19_000.0, 3, 10_000.0; "Range containing zero")]
+    #[test_case(-1.0, 1.0, 2, -0.5; "Correlation coefficient")]
+    fn round_trip(min: f64, max: f64, length: usize, value: f64) {
+        let bytes = to_imapb(min, max, length, value);
+        let decoded = imapb(min, max, &bytes).unwrap();
+        let (_, resolution, _) = super::parameters(min, max, length);
+        assert!(
+            (decoded - value).abs() <= resolution,
+            "Decoded {} but expected {}",
+            decoded,
+            value
+        );
+    }
+
+    #[test]
+    fn special_values() {
+        assert_eq!(imapb(0.0, 1.0, &[0xC0, 0x00]), Some(f64::INFINITY));
+        assert_eq!(imapb(0.0, 1.0, &[0xE0, 0x00]), Some(f64::NEG_INFINITY));
+        assert!(imapb(0.0, 1.0, &[0xD0, 0x00]).unwrap().is_nan());
+    }
+}
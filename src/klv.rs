@@ -5,11 +5,18 @@ use tracing::info;
 #[cfg(feature = "tracing")]
 use tracing::trace;
 
+use alloc::boxed::Box;
+
+use alloc::vec::Vec;
+
 use crate::ErrorKind;
+use crate::imap;
 use crate::klv_value::KlvValue;
+use crate::sdcc::Sdcc;
+use crate::tag::Length;
 use crate::tag::Tag;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Klv {
     tag: Tag,
     value: KlvValue,
@@ -17,6 +24,12 @@ pub struct Klv {
 
 impl Klv {
     pub fn new(tag_id: usize, raw_value: Box<[u8]>) -> Result<Klv, ErrorKind> {
+        Klv::from_raw(tag_id, &raw_value)
+    }
+
+    /// Decode the value of `tag_id` from `raw_value` without taking
+    /// ownership of it.
+    pub fn from_raw(tag_id: usize, raw_value: &[u8]) -> Result<Klv, ErrorKind> {
         // Convert the tag ID into the tag variant it corresponds to
         let tag = Tag::from(tag_id);
 
@@ -28,11 +41,18 @@ impl Klv {
             return Err(ErrorKind::UnsupportedTag(tag_id));
         }
 
-        let value = KlvValue::from_bytes(tag, &raw_value)?;
+        let value = KlvValue::from_bytes(tag, raw_value)?;
 
         Ok(Klv { tag, value })
     }
 
+    /// Create a field from a value that has already been decoded.
+    ///
+    /// The value isn't checked against the type of `tag`.
+    pub fn from_value(tag: Tag, value: KlvValue) -> Klv {
+        Klv { tag, value }
+    }
+
     pub fn tag(&self) -> Tag {
         self.tag
     }
@@ -40,4 +60,98 @@ impl Klv {
     pub fn value(&self) -> &KlvValue {
         &self.value
     }
+
+    /// Encode the value of the field.
+    ///
+    /// IMAPB values are mapped onto the range of the tag at the longest
+    /// length it allows. Other values are encoded by [`KlvValue::to_bytes`].
+    pub fn to_bytes(&self) -> Option<Vec<u8>> {
+        let KlvValue::IMAPB(value) = self.value else {
+            return self.value.to_bytes();
+        };
+        let info = self.tag.info();
+        let (min, max) = info.range?;
+        let length = match info.length {
+            Length::Fixed(length) | Length::Max(length) => length.min(8),
+            Length::Variable => 8,
+        };
+        Some(imap::to_imapb(min, max, length, value))
+    }
+}
+
+/// Conversion of a field into the type returned by its accessor on
+/// [`KlvPacket`](crate::klv_packet::KlvPacket).
+pub(crate) trait FromKlv<'a>: Sized {
+    /// Returns `None` if the value of `field` is the wrong type or invalid.
+    fn from_klv(field: &'a Klv) -> Option<Self>;
+}
+
+/// Return the value of `field` if it is any of the unsigned integer types
+fn uint(field: &Klv) -> Option<u64> {
+    match *field.value() {
+        KlvValue::Uint(value) | KlvValue::Uint64(value) => Some(value),
+        KlvValue::Uint32(value) => Some(value.into()),
+        KlvValue::Uint16(value) => Some(value.into()),
+        KlvValue::Uint8(value) => Some(value.into()),
+        _ => None,
+    }
+}
+
+impl FromKlv<'_> for u8 {
+    fn from_klv(field: &Klv) -> Option<Self> {
+        uint(field)?.try_into().ok()
+    }
+}
+
+impl FromKlv<'_> for u16 {
+    fn from_klv(field: &Klv) -> Option<Self> {
+        uint(field)?.try_into().ok()
+    }
+}
+
+impl FromKlv<'_> for u64 {
+    fn from_klv(field: &Klv) -> Option<Self> {
+        uint(field)
+    }
+}
+
+impl FromKlv<'_> for i64 {
+    fn from_klv(field: &Klv) -> Option<Self> {
+        match *field.value() {
+            KlvValue::Int(value) => Some(value),
+            KlvValue::Int32(value) => Some(value.into()),
+            KlvValue::Int16(value) => Some(value.into()),
+            KlvValue::Int8(value) => Some(value.into()),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> FromKlv<'a> for &'a str {
+    fn from_klv(field: &'a Klv) -> Option<Self> {
+        match field.value() {
+            KlvValue::Utf8(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> FromKlv<'a> for &'a [u8] {
+    fn from_klv(field: &'a Klv) -> Option<Self> {
+        match field.value() {
+            KlvValue::Byte(bytes)
+            | KlvValue::VLP(bytes)
+            | KlvValue::DLP(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> FromKlv<'a> for &'a Sdcc {
+    fn from_klv(field: &'a Klv) -> Option<Self> {
+        match field.value() {
+            KlvValue::FLP(sdcc) => Some(sdcc),
+            _ => None,
+        }
+    }
 }
@@ -1,288 +1,415 @@
-use std::io::Cursor;
-use std::io::Read;
-use std::sync::Arc;
-
-use bitvec::field::BitField;
-use bitvec::order::Msb0;
-use bitvec::view::BitView;
-#[cfg(feature = "search")]
-use memmem::Searcher;
-#[cfg(feature = "search")]
-use memmem::TwoWaySearcher;
+use alloc::vec::Vec;
+use core::num::NonZeroU32;
+
 #[cfg(feature = "tracing")]
 use tracing::debug;
-#[cfg(feature = "tracing")]
-use tracing::info;
-#[cfg(feature = "tracing")]
-use tracing::trace;
 
 use crate::ErrorKind;
+use crate::cursor::Cursor;
+use crate::flags::ActivePayloads;
+use crate::flags::GenericFlags;
+use crate::flags::IcingDetected;
+use crate::flags::OperationalMode;
+use crate::flags::PlatformStatus;
+use crate::flags::PositionMethodSource;
+use crate::flags::SensorControlMode;
+use crate::flags::SensorFieldOfViewName;
+use crate::klv::FromKlv;
 use crate::klv::Klv;
+use crate::klv_packet_ref::KlvPacketRef;
 use crate::klv_value::KlvValue;
+use crate::local_set;
+use crate::local_set::ChecksumPolicy;
+use crate::local_set::ChecksumRule;
+use crate::local_set::ChecksumStatus;
+use crate::local_set::Encoding;
+use crate::miis::MiisCoreId;
+use crate::options::DuplicatePolicy;
+use crate::options::ParseOptions;
 use crate::tag::Tag;
+use crate::weapon::StoreLocation;
+use crate::weapon::WeaponFired;
+use crate::weapon::WeaponLoad;
+use crate::weapon::WeaponStore;
 
 pub const UAS_LOCAL_SET_UNIVERSAL_LABEL: [u8; 16] = [
     0x06, 0x0E, 0x2B, 0x34, 0x02, 0x0B, 0x01, 0x01, 0x0E, 0x01, 0x03, 0x01,
     0x01, 0x00, 0x00, 0x00,
 ];
 
-#[derive(Clone, Debug)]
+/// The UAS LS checksum is a running 16-bit sum stored in tag 1.
+pub(crate) const UAS_CHECKSUM: ChecksumRule = ChecksumRule::Bcc16 { tag: 1 };
+
+/// UAS LS tags are BER-OID encoded, so tags above 127 take two bytes.
+pub(crate) const UAS_TAG_ENCODING: Encoding = Encoding::BerOid;
+
+/// UAS LS lengths use the BER short or long form.
+pub(crate) const UAS_LENGTH_ENCODING: Encoding = Encoding::Ber;
+
+/// A tag that appears more than once in a packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Duplicate {
+    pub tag: Tag,
+    /// Number of times the tag appears.
+    pub count: usize,
+    /// Whether the occurrences don't all have the same value.
+    pub conflicting: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct KlvPacket {
+    /// Fields in the order they were received, so the packet can be encoded
+    /// again unchanged.
     fields: Vec<Klv>,
+    /// Position in `fields` of the field returned for each tag ID, plus one.
+    index: [Option<NonZeroU32>; Tag::COUNT],
 }
 
 impl KlvPacket {
-    /// Get the next tag from these bytes. This handles both non-BER and BER
-    /// length values.
-    ///
-    /// The first byte in the `bytes` slice should be the start of the next tag.
-    fn get_tag(buf: &mut Cursor<&[u8]>) -> usize {
-        Self::get_ber_value(buf)
-    }
-
-    /// Get the length of a field. This handles both non-BER and BER length
-    /// values.
-    fn get_length(buf: &mut Cursor<&[u8]>) -> usize {
-        Self::get_ber_value(buf)
-    }
-
     /// Get the BER value from the bytes given
     ///
     /// The first byte in the `bytes` slice should be the start of the BER
     /// sequence.
-    fn get_ber_value(buf: &mut Cursor<&[u8]>) -> usize {
-        // Buffer for reading bytes
-        let mut new_byte: [u8; 1] = [0];
-        // Get the first byte so we can evaluate if we need more
-        buf.read_exact(&mut new_byte).expect("Can't read from bytes");
-
-        // If the first bit is a 1 then this is a long-form BER.
-        let bits = new_byte.view_bits::<Msb0>();
-        let msb =
-            bits.get(0).expect("Cannot get the first bit from the byte array");
-
-        if msb == true {
-            let Some(remainder) = bits.get(1..bits.len()) else {
-                panic!("Cannot get bits after first for BER byte")
-            };
-            let long_length = remainder.load_be();
-            // Read all of the length bytes determined from the first length
-            // byte
-            let mut len_bytes = vec![0; long_length];
-            buf.read_exact(&mut len_bytes).expect("Can't read from bytes");
-
-            return len_bytes.view_bits::<Msb0>().load_be();
-        }
-
-        bits.load_be()
+    pub(crate) fn get_ber_value(
+        buf: &mut Cursor<&[u8]>,
+    ) -> Result<usize, ErrorKind> {
+        local_set::read_ber(buf)
     }
 
-    fn get_value(
+    /// Get the BER-OID value from the bytes given
+    ///
+    /// Each byte holds 7 bits of the value. The most significant bit of each
+    /// byte is set when another byte follows.
+    pub(crate) fn get_ber_oid_value(
         buf: &mut Cursor<&[u8]>,
-        tag: usize,
-        length: usize,
-    ) -> Result<Klv, ErrorKind> {
-        let mut value_buf = vec![0; length];
-        buf.read_exact(&mut value_buf)
-            .expect("Couldn't read all value data necessary from buffer");
-        Klv::new(tag, value_buf.into())
-    }
-
-    // This function calculated the checksum of the packet buffer passed in.
-    // This should be the entire packet, starting with the UAS LS Key and
-    // ending with the calculated checksum.
-    fn calculate_checksum(buf: &[u8]) -> u16 {
-        let mut bcc: u16 = 0;
-        buf[..buf.len()].iter().enumerate().for_each(|(idx, byte)| {
-            bcc =
-                bcc.wrapping_add((*byte as u16) << (8 * ((idx + 1) % 2)) as u16)
-        });
-        bcc
+    ) -> Result<usize, ErrorKind> {
+        local_set::read_ber_oid(buf)
     }
 
     /// Parse the bytes into a usable KLV packet
     pub fn from_bytes(bytes: &[u8]) -> Result<Option<KlvPacket>, ErrorKind> {
-        let start_index: usize;
-
-        #[cfg(feature = "search")]
-        {
-            let search = TwoWaySearcher::new(&UAS_LOCAL_SET_UNIVERSAL_LABEL);
-            start_index = match search.search_in(&bytes) {
-                Some(idx) => idx,
-                None => return Ok(None),
-            };
-        }
-        #[cfg(not(feature = "search"))]
-        {
-            // Check if the first bytes are exactly the magic number from the
-            // UAS LS Label.
-            let test_bytes = &bytes[0..UAS_LOCAL_SET_UNIVERSAL_LABEL.len()];
-            if !test_bytes.iter().eq(UAS_LOCAL_SET_UNIVERSAL_LABEL.iter()) {
-                return Ok(None);
-            }
-
-            start_index = 0;
-        }
+        Ok(KlvPacket::from_bytes_with_checksum(bytes, ChecksumPolicy::Strict)?
+            .map(|(packet, _)| packet))
+    }
 
-        #[cfg(feature = "tracing")]
-        {
-            trace!("Parsing KLV packet: {:02X?}", bytes);
-            trace!("Start index [{}]", start_index);
-        }
+    /// Parse the bytes into a usable KLV packet, handling a missing or wrong
+    /// checksum according to `policy`.
+    ///
+    /// The packet is returned along with the status of its checksum, which is
+    /// always [`ChecksumStatus::Valid`] for [`ChecksumPolicy::Strict`].
+    pub fn from_bytes_with_checksum(
+        bytes: &[u8],
+        policy: ChecksumPolicy,
+    ) -> Result<Option<(KlvPacket, ChecksumStatus)>, ErrorKind> {
+        KlvPacket::parse_with(
+            bytes,
+            &ParseOptions { checksum: policy, ..ParseOptions::default() },
+        )
+    }
 
-        // This is the position that the length bytes for the entire packet
-        // start at.
-        let length_position = start_index + UAS_LOCAL_SET_UNIVERSAL_LABEL.len();
+    /// Parse the bytes into a usable KLV packet using `options`.
+    ///
+    /// The packet is returned along with the status of its checksum, which is
+    /// always [`ChecksumStatus::Valid`] for [`ChecksumPolicy::Strict`].
+    pub fn parse_with(
+        bytes: &[u8],
+        options: &ParseOptions,
+    ) -> Result<Option<(KlvPacket, ChecksumStatus)>, ErrorKind> {
+        let Some(packet) = KlvPacketRef::parse_with(bytes, options)? else {
+            return Ok(None);
+        };
+        let checksum = packet.checksum_status();
+        let packet = packet.to_packet_with(options)?;
 
         #[cfg(feature = "tracing")]
-        trace!("Length position [{}]", length_position);
+        debug!(
+            "Found KLV packet with timestamp {:?} and checksum {:?}",
+            packet.get_ref(Tag::PrecisionTimeStamp).map(|field| field.value()),
+            checksum
+        );
 
-        // Create a cursor for the bytes so we can keep track of what has been
-        // read without a bunch of magic numbers.
-        let mut buffer = Cursor::new(bytes);
-        buffer.set_position(length_position as u64);
+        Ok(Some((packet, checksum)))
+    }
 
-        // Get the length of the UAS Datalink Packet Value field.
-        let klv_length = Self::get_length(&mut buffer);
-        #[cfg(feature = "tracing")]
-        trace!("Length of packet [{}]", klv_length);
+    /// Recalculate the checksum of the first UAS LS KLV packet in `bytes` and
+    /// write it into the packet, returning the new checksum.
+    ///
+    /// This is for data from encoders that calculate the checksum wrongly.
+    /// The packet must already end with a checksum field. Returns `Ok(None)`
+    /// if there is no packet in `bytes`.
+    pub fn repair_checksum(bytes: &mut [u8]) -> Result<Option<u16>, ErrorKind> {
+        KlvPacket::repair_checksum_with(bytes, &ParseOptions::default())
+    }
 
-        // Need to keep track of how many bytes make up the length field as this
-        // is used when grabbing the bytes that are used to calculate
-        // the checksum
-        let length_field_length = buffer.position() as usize - length_position;
+    /// Recalculate the checksum of the first UAS LS KLV packet in `bytes`,
+    /// only looking past the start of `bytes` if [`ParseOptions::search`] is
+    /// set.
+    pub fn repair_checksum_with(
+        bytes: &mut [u8],
+        options: &ParseOptions,
+    ) -> Result<Option<u16>, ErrorKind> {
+        local_set::repair_checksum(
+            bytes,
+            &UAS_LOCAL_SET_UNIVERSAL_LABEL,
+            UAS_LENGTH_ENCODING,
+            UAS_TAG_ENCODING,
+            UAS_LENGTH_ENCODING,
+            UAS_CHECKSUM,
+            options.search,
+        )
+    }
 
-        #[cfg(feature = "tracing")]
-        trace!("Length field length [{}]", length_field_length);
+    /// Build a packet from its fields. Accessors return the first field for
+    /// each tag.
+    pub fn from_fields(fields: Vec<Klv>) -> KlvPacket {
+        KlvPacket::from_fields_with(fields, DuplicatePolicy::First)
+    }
 
-        // Index in the data where KLV data ends
-        let klv_packet_end = length_position + length_field_length + klv_length;
+    /// Encode the packet, ending with a checksum calculated over the encoded
+    /// bytes. Any checksum field in the packet is replaced.
+    ///
+    /// Fails with [`ErrorKind::Unimplemented`] if a value can't be encoded.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ErrorKind> {
+        let mut body = Vec::new();
+        for field in self.fields.iter().filter(|f| f.tag() != Tag::Checksum) {
+            let tag = field.tag().id();
+            let value =
+                field.to_bytes().ok_or(ErrorKind::Unimplemented(tag))?;
+            UAS_TAG_ENCODING.write(tag, &mut body)?;
+            UAS_LENGTH_ENCODING.write(value.len(), &mut body)?;
+            body.extend_from_slice(&value);
+        }
+        // The checksum covers its own tag and length.
+        UAS_TAG_ENCODING.write(Tag::Checksum.id(), &mut body)?;
+        UAS_LENGTH_ENCODING.write(2, &mut body)?;
+
+        let mut bytes = Vec::from(UAS_LOCAL_SET_UNIVERSAL_LABEL);
+        UAS_LENGTH_ENCODING.write(body.len() + 2, &mut bytes)?;
+        bytes.extend_from_slice(&body);
+        let checksum =
+            UAS_CHECKSUM.calculate(&bytes).ok_or(ErrorKind::InvalidChecksum)?;
+        bytes.extend_from_slice(&checksum.to_be_bytes());
+        Ok(bytes)
+    }
 
-        #[cfg(feature = "tracing")]
-        trace!("KLV packet end [{}]", klv_packet_end);
+    /// Build a packet whose accessors return the field for each tag chosen by
+    /// `duplicates`. [`DuplicatePolicy::Reject`] is treated as
+    /// [`DuplicatePolicy::First`] here.
+    pub(crate) fn from_fields_with(
+        fields: Vec<Klv>,
+        duplicates: DuplicatePolicy,
+    ) -> KlvPacket {
+        let mut index = [None; Tag::COUNT];
+        for (position, field) in fields.iter().enumerate() {
+            let entry = &mut index[field.tag().id()];
+            if entry.is_none() || duplicates == DuplicatePolicy::Last {
+                *entry =
+                    u32::try_from(position + 1).ok().and_then(NonZeroU32::new);
+            }
+        }
+        KlvPacket { fields, index }
+    }
 
-        // Get the number of Tag variants that are currently supported.
-        let max_tag_id = Tag::COUNT;
+    /// Every field in the packet, in the order they were received.
+    pub fn fields(&self) -> impl Iterator<Item = &Klv> {
+        self.fields.iter()
+    }
 
-        let mut fields = Vec::new();
+    /// Get a reference to the field for `tag` without cloning it.
+    pub fn get_ref(&self, tag: Tag) -> Option<&Klv> {
+        self.get_ref_id(tag.id())
+    }
 
-        while buffer.position() < klv_packet_end as u64 {
-            let tag = Self::get_tag(&mut buffer);
-            // If the tag is larger than the known max tag ID then we know it's
-            // not supported
-            if tag > max_tag_id {
-                return Err(ErrorKind::UnsupportedTag(tag));
-            }
+    /// Get a reference to the field with the tag ID `tag` without cloning it.
+    pub fn get_ref_id(&self, tag: usize) -> Option<&Klv> {
+        let position = self.index.get(tag).copied().flatten()?;
+        self.fields.get(position.get() as usize - 1)
+    }
 
-            let length = Self::get_length(&mut buffer);
+    pub fn get_id(&self, tag: usize) -> Option<Klv> {
+        self.get_ref_id(tag).cloned()
+    }
 
-            // Continue on to the next field if the length of this one is 0
-            if length == 0 {
-                #[cfg(feature = "tracing")]
-                debug!("Length of tag [{}] is 0", tag);
-                continue;
-            }
+    pub fn get(&self, tag: Tag) -> Option<Klv> {
+        self.get_id(tag.into())
+    }
 
-            let value = Self::get_value(&mut buffer, tag, length)?;
+    /// Every field for `tag`, in the order they were received.
+    pub fn get_all(&self, tag: Tag) -> impl Iterator<Item = &Klv> {
+        self.fields.iter().filter(move |field| field.tag() == tag)
+    }
 
-            #[cfg(feature = "tracing")]
-            trace!(
-                "Added tag to KLV packet: [{}]",
-                Into::<&'static str>::into(value.tag())
-            );
-            fields.push(value);
+    /// Every tag that appears more than once in the packet, in tag order.
+    pub fn duplicates(&self) -> Vec<Duplicate> {
+        let mut counts = [0; Tag::COUNT];
+        for field in &self.fields {
+            counts[field.tag().id()] += 1;
         }
 
-        let packet = KlvPacket { fields };
+        Tag::iter()
+            .filter(|tag| counts[tag.id()] > 1)
+            .map(|tag| {
+                let first = self.get_all(tag).next().map(Klv::value);
+                Duplicate {
+                    tag,
+                    count: counts[tag.id()],
+                    conflicting: self
+                        .get_all(tag)
+                        .any(|field| Some(field.value()) != first),
+                }
+            })
+            .collect()
+    }
 
-        let packet_checksum = packet.checksum();
+    /// Convert the field for `tag` into the type returned by its accessor
+    pub(crate) fn typed<'a, T: FromKlv<'a>>(&'a self, tag: Tag) -> Option<T> {
+        T::from_klv(self.get_ref(tag)?)
+    }
 
-        let checksum_bytes_length = klv_packet_end - 2;
-        let calculated_checksum = Self::calculate_checksum(
-            bytes.get(start_index..checksum_bytes_length).unwrap(),
-        );
+    /// Return the latitude and longitude of the sensor in degrees
+    pub fn sensor_location(&self) -> Option<(f64, f64)> {
+        Some((self.sensor_latitude()?.0, self.sensor_longitude()?.0))
+    }
 
-        if packet_checksum != calculated_checksum {
-            #[cfg(feature = "tracing")]
-            debug!(
-                "Checksum for packet [{}] vs calculated checksum [{}]",
-                packet_checksum, calculated_checksum
-            );
-            return Err(ErrorKind::InvalidChecksum);
-        }
+    /// Return the latitude and longitude of the center of the frame in degrees
+    pub fn frame_center(&self) -> Option<(f64, f64)> {
+        Some((
+            self.frame_center_latitude()?.0,
+            self.frame_center_longitude()?.0,
+        ))
+    }
 
-        #[cfg(feature = "tracing")]
-        debug!(
-            "Found valid KLV packet with timestamp {}",
-            packet.precision_time_stamp()
-        );
+    /// Return the store that was fired by the UAS LS KLV packet, found at the
+    /// default [`StoreLocation`] of the weapon fired
+    pub fn weapon_fired_store(&self) -> Option<WeaponStore> {
+        let location = StoreLocation::from(self.weapon_fired()?);
+        self.weapon_stores()?.into_iter().find(|store| store.is_at(location))
+    }
 
-        Ok(Some(packet))
+    /// Return the standard deviation reported for the value of `tag`
+    pub fn standard_deviation(&self, tag: Tag) -> Option<f64> {
+        self.sdcc()?.standard_deviation(tag)
     }
 
-    pub fn get_id(&self, tag: usize) -> Option<Klv> {
-        self.fields
-            .iter()
-            .find(|field_tag| tag == field_tag.tag().into())
-            .cloned()
+    /// Return the covariance reported between the values of the two tags
+    pub fn covariance(&self, a: Tag, b: Tag) -> Option<f64> {
+        self.sdcc()?.covariance(a, b)
     }
+}
 
-    pub fn get(&self, tag: Tag) -> Option<Klv> {
-        self.get_id(tag.into())
+impl FromKlv<'_> for GenericFlags {
+    fn from_klv(field: &Klv) -> Option<Self> {
+        Some(GenericFlags::new(u8::from_klv(field)?))
     }
+}
 
-    /// Return the checksum of this UAS LS KLV packet
-    pub fn checksum(&self) -> u16 {
-        match self
-            .get(Tag::Checksum)
-            .expect("KLV packets must have a checksum")
-            .value()
-        {
-            KlvValue::Uint16(value) => *value,
-            _ => panic!(
-                "This packet does not have a checksum and that error was not caught. This should be unreachable"
-            ),
+impl FromKlv<'_> for IcingDetected {
+    fn from_klv(field: &Klv) -> Option<Self> {
+        IcingDetected::from_repr(u8::from_klv(field)?)
+    }
+}
+
+impl FromKlv<'_> for SensorFieldOfViewName {
+    fn from_klv(field: &Klv) -> Option<Self> {
+        SensorFieldOfViewName::from_repr(u8::from_klv(field)?)
+    }
+}
+
+impl FromKlv<'_> for OperationalMode {
+    fn from_klv(field: &Klv) -> Option<Self> {
+        OperationalMode::from_repr(u8::from_klv(field)?)
+    }
+}
+
+impl FromKlv<'_> for PlatformStatus {
+    fn from_klv(field: &Klv) -> Option<Self> {
+        PlatformStatus::from_repr(u8::from_klv(field)?)
+    }
+}
+
+impl FromKlv<'_> for SensorControlMode {
+    fn from_klv(field: &Klv) -> Option<Self> {
+        SensorControlMode::from_repr(u8::from_klv(field)?)
+    }
+}
+
+impl FromKlv<'_> for PositionMethodSource {
+    fn from_klv(field: &Klv) -> Option<Self> {
+        Some(PositionMethodSource::new(u64::from_klv(field)?))
+    }
+}
+
+impl FromKlv<'_> for ActivePayloads {
+    fn from_klv(field: &Klv) -> Option<Self> {
+        match field.value() {
+            KlvValue::Byte(bytes) => Some(ActivePayloads::new(bytes)),
+            _ => None,
         }
     }
+}
+
+impl FromKlv<'_> for WeaponLoad {
+    fn from_klv(field: &Klv) -> Option<Self> {
+        Some(WeaponLoad::from(u16::from_klv(field)?))
+    }
+}
+
+impl FromKlv<'_> for WeaponFired {
+    fn from_klv(field: &Klv) -> Option<Self> {
+        Some(WeaponFired::from(u8::from_klv(field)?))
+    }
+}
 
-    /// Return the precision time stamp of the UAS LS KLV packet
-    pub fn precision_time_stamp(&self) -> u64 {
-        match self
-            .get(Tag::PrecisionTimeStamp)
-            .expect("KLV packets must have a precision time stamp")
-            .value()
-        {
-            KlvValue::Uint64(value) => *value,
-            _ => panic!(
-                "This packet does not have a checksum and that error was not caught. This should be unreachable"
-            ),
+/// Weapons stores that are malformed are treated as missing.
+impl FromKlv<'_> for Vec<WeaponStore> {
+    fn from_klv(field: &Klv) -> Option<Self> {
+        match field.value() {
+            KlvValue::VLP(bytes) => WeaponStore::from_series(bytes).ok(),
+            _ => None,
         }
     }
+}
 
-    /// Return the precision time stamp of the UAS LS KLV packet
-    pub fn mission_id(&self) -> Option<Arc<str>> {
-        match self.get(Tag::MissionID)?.value() {
-            KlvValue::Utf8(value) => Some(value.clone()),
-            _ => panic!(
-                "This packet does not have a mission ID and that error was not caught. This should be unreachable"
-            ),
+/// Core Identifiers that are malformed are treated as missing.
+impl FromKlv<'_> for MiisCoreId {
+    fn from_klv(field: &Klv) -> Option<Self> {
+        match field.value() {
+            KlvValue::Byte(bytes) => MiisCoreId::from_bytes(bytes).ok(),
+            _ => None,
         }
     }
 }
 
 #[cfg(test)]
-mod tests {
-    use std::io::Cursor;
-    use std::sync::Arc;
+pub(crate) mod tests {
     use std::vec;
 
     use itertools::chain;
+    use proptest::prelude::*;
+    use proptest::sample::Index;
     use test_case::test_case;
 
+    use super::Duplicate;
     use super::KlvPacket;
+    use super::UAS_CHECKSUM;
     use super::UAS_LOCAL_SET_UNIVERSAL_LABEL;
-
-    fn packet_from_value(test_value: Vec<u8>) -> Vec<u8> {
+    use crate::ErrorKind;
+    use crate::cursor::Cursor;
+    use crate::flags::PlatformStatus;
+    use crate::klv::Klv;
+    use crate::klv_value::KlvValue;
+    use crate::klv_value::tests::ENCODABLE;
+    use crate::klv_value::tests::value;
+    use crate::local_set::ChecksumPolicy;
+    use crate::local_set::ChecksumStatus;
+    use crate::options::DuplicatePolicy;
+    use crate::options::LengthPolicy;
+    use crate::options::ParseOptions;
+    use crate::options::UnimplementedPolicy;
+    use crate::options::UnknownTagPolicy;
+    use crate::tag::Tag;
+
+    pub(crate) fn packet_from_value(test_value: Vec<u8>) -> Vec<u8> {
         let precision_timestamp_bytes =
             vec![0x02, 0x08, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77];
         let checksum_header = vec![0x01, 0x02];
@@ -301,7 +428,7 @@ mod tests {
             checksum_header
         )
         .collect();
-        let checksum = KlvPacket::calculate_checksum(&packet_minus_checksum);
+        let checksum = UAS_CHECKSUM.calculate(&packet_minus_checksum).unwrap();
 
         chain!(
             Vec::from(packet_minus_checksum),
@@ -310,33 +437,477 @@ mod tests {
         .collect()
     }
 
+    /// Build a packet with the given precision time stamp followed by
+    /// `fields`.
+    pub(crate) fn packet_at(timestamp: u64, fields: &[u8]) -> KlvPacket {
+        let mut bytes = Vec::from(UAS_LOCAL_SET_UNIVERSAL_LABEL);
+        bytes.push((fields.len() + 14).try_into().unwrap());
+        bytes.extend_from_slice(&[0x02, 0x08]);
+        bytes.extend_from_slice(&timestamp.to_be_bytes());
+        bytes.extend_from_slice(fields);
+        bytes.extend_from_slice(&[0x01, 0x02]);
+        let checksum = UAS_CHECKSUM.calculate(&bytes).unwrap();
+        bytes.extend_from_slice(&checksum.to_be_bytes());
+        KlvPacket::from_bytes(&bytes).unwrap().unwrap()
+    }
+
     fn packet_1() -> Vec<u8> {
         packet_from_value(vec![0x03, 0x02, b'I', b'D']) // Mission ID is 'ID'.
     }
 
-    #[test_case(packet_1, 47467, Some("ID".into()))]
+    #[test]
+    fn flags() {
+        // Generic flag data with the laser on and platform status of egress.
+        let bytes = packet_from_value(vec![0x2F, 0x01, 0x01, 0x7D, 0x01, 0x09]);
+        let packet = KlvPacket::from_bytes(&bytes).unwrap().unwrap();
+        assert!(packet.generic_flags().unwrap().laser_on());
+        assert_eq!(packet.platform_status(), Some(PlatformStatus::Egress));
+        assert_eq!(packet.operational_mode(), None);
+    }
+
+    #[test]
+    fn miis_core_identifier() {
+        let mut value = vec![0x5E, 0x12, 0x01, 0x40];
+        value.extend_from_slice(&[0xAB; 16]);
+        let bytes = packet_from_value(value);
+        let packet = KlvPacket::from_bytes(&bytes).unwrap().unwrap();
+        let id = packet.miis_core_identifier().unwrap();
+        assert_eq!(id.sensor_id(), Some(&[0xAB; 16]));
+        assert_eq!(id.platform_id(), None);
+    }
+
+    #[test_case(packet_1, 47467, Some("ID"))]
     fn from_bytes(
         packet: fn() -> Vec<u8>,
         checksum: u16,
-        mission_id: Option<Arc<str>>,
+        mission_id: Option<&str>,
     ) {
         let bytes = packet();
-        let packet = KlvPacket::from_bytes(bytes.into()).unwrap().unwrap();
-        assert_eq!(packet.checksum(), checksum, "Checksum is incorrect");
+        let packet = KlvPacket::from_bytes(&bytes).unwrap().unwrap();
+        assert_eq!(packet.checksum(), Some(checksum), "Checksum is incorrect");
         assert_eq!(
             packet.precision_time_stamp(),
-            4822678189205111,
+            Some(4822678189205111),
             "Precision Time Stamp is incorrect"
         );
         assert_eq!(packet.mission_id(), mission_id)
     }
 
+    #[test]
+    fn checksum_policy() {
+        let mut bytes = packet_1();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+
+        assert!(matches!(
+            KlvPacket::from_bytes(&bytes),
+            Err(ErrorKind::InvalidChecksum)
+        ));
+        let (packet, status) = KlvPacket::from_bytes_with_checksum(
+            &bytes,
+            ChecksumPolicy::Lenient,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(packet.mission_id(), Some("ID"));
+        assert_eq!(
+            status,
+            ChecksumStatus::Mismatch { found: 47467 ^ 0xFF, calculated: 47467 }
+        );
+
+        assert_eq!(
+            KlvPacket::repair_checksum(&mut bytes).unwrap(),
+            Some(47467)
+        );
+        assert_eq!(bytes, packet_1());
+
+        // The packet is only found after other data when searching.
+        let mut bytes = [&[0x00][..], &packet_1()].concat();
+        let options = ParseOptions { search: false, ..ParseOptions::default() };
+        let repaired = KlvPacket::repair_checksum_with(&mut bytes, &options);
+        assert_eq!(repaired.unwrap(), None);
+        let options = ParseOptions { search: true, ..options };
+        let repaired = KlvPacket::repair_checksum_with(&mut bytes, &options);
+        assert_eq!(repaired.unwrap(), Some(47467));
+    }
+
+    #[test]
+    fn missing_checksum() {
+        // A packet with only a precision time stamp.
+        let mut bytes = Vec::from(UAS_LOCAL_SET_UNIVERSAL_LABEL);
+        bytes.extend_from_slice(&[0x0A, 0x02, 0x08, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+        let (packet, status) = KlvPacket::from_bytes_with_checksum(
+            &bytes,
+            ChecksumPolicy::Lenient,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(status, ChecksumStatus::Missing);
+        assert_eq!(packet.checksum(), None);
+        assert!(KlvPacket::repair_checksum(&mut bytes).is_err());
+    }
+
+    #[test]
+    fn defined_length_pack() {
+        // Image horizon pixel pack, kept as raw bytes.
+        let bytes = packet_from_value(vec![0x51, 0x02, 0x10, 0x20]);
+        let packet = KlvPacket::from_bytes(&bytes).unwrap().unwrap();
+        assert_eq!(packet.image_horizon_pixel_pack(), Some(&[0x10, 0x20][..]));
+        assert_eq!(packet.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn extended_tags() {
+        // Target ID 'T1', Leap Seconds of 18 and a Metadata Substream ID.
+        let bytes = packet_from_value(vec![
+            0x81, 0x01, 0x02, b'T', b'1', 0x81, 0x08, 0x01, 0x12, 0x81, 0x0F,
+            0x01, 0xAA,
+        ]);
+        let packet = KlvPacket::from_bytes(&bytes).unwrap().unwrap();
+        assert_eq!(packet.target_id(), Some("T1"));
+        assert_eq!(packet.leap_seconds(), Some(18));
+        assert_eq!(packet.metadata_substream_id_pack(), Some(&[0xAA][..]));
+        assert!(packet.fields().all(|field| field.tag() != Tag::Unknown));
+
+        assert_eq!(packet.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn index() {
+        // Two mission IDs and a platform heading angle.
+        let packet = packet_at(
+            1,
+            &[0x03, 0x01, b'A', 0x05, 0x02, 0x00, 0x01, 0x03, 0x01, b'B'],
+        );
+        assert_eq!(packet.mission_id(), Some("A"));
+        assert_eq!(
+            packet
+                .get_ref(Tag::PlatformHeadingAngle)
+                .map(|field| field.value()),
+            Some(&KlvValue::Uint16(1))
+        );
+        assert!(packet.get_ref(Tag::PlatformTailNumber).is_none());
+        assert!(packet.get_ref_id(Tag::COUNT).is_none());
+        assert_eq!(
+            packet.fields().map(|field| field.tag()).collect::<Vec<_>>(),
+            [
+                Tag::PrecisionTimeStamp,
+                Tag::MissionID,
+                Tag::PlatformHeadingAngle,
+                Tag::MissionID,
+                Tag::Checksum
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicates() {
+        // Mission ID of `A`, heading, then a mission ID of `B` and the same
+        // heading again.
+        let mut bytes = Vec::from(UAS_LOCAL_SET_UNIVERSAL_LABEL);
+        bytes.extend_from_slice(&[
+            0x12, 0x03, 0x01, b'A', 0x05, 0x02, 0x00, 0x01, 0x03, 0x01, b'B',
+            0x05, 0x02, 0x00, 0x01, 0x01, 0x02,
+        ]);
+        let checksum = UAS_CHECKSUM.calculate(&bytes).unwrap();
+        bytes.extend_from_slice(&checksum.to_be_bytes());
+        let parse = |duplicates| {
+            let options =
+                ParseOptions { duplicates, ..ParseOptions::default() };
+            KlvPacket::parse_with(&bytes, &options).map(|packet| {
+                let (packet, _) = packet.unwrap();
+                packet
+            })
+        };
+
+        let packet = parse(DuplicatePolicy::First).unwrap();
+        assert_eq!(packet.mission_id(), Some("A"));
+        assert_eq!(packet.get_all(Tag::MissionID).count(), 2);
+        assert_eq!(
+            packet.duplicates(),
+            [
+                Duplicate { tag: Tag::MissionID, count: 2, conflicting: true },
+                Duplicate {
+                    tag: Tag::PlatformHeadingAngle,
+                    count: 2,
+                    conflicting: false
+                },
+            ]
+        );
+
+        let packet = parse(DuplicatePolicy::Last).unwrap();
+        assert_eq!(packet.mission_id(), Some("B"));
+        assert!(matches!(
+            parse(DuplicatePolicy::Reject),
+            Err(ErrorKind::DuplicateTag(3))
+        ));
+    }
+
+    #[test]
+    fn parse_with() {
+        // Local sets can't be decoded yet, and tag 0 is not a UAS LS tag.
+        let bytes = chain!(
+            [0xFF, 0x00],
+            packet_from_value(vec![0x30, 0x01, 0x00, 0x00, 0x01, 0x00])
+        )
+        .collect::<Vec<_>>();
+        let parse = |options: &ParseOptions| {
+            KlvPacket::parse_with(&bytes, options).map(|packet| {
+                packet.map(|(packet, _)| {
+                    packet.fields().map(|field| field.tag()).collect::<Vec<_>>()
+                })
+            })
+        };
+
+        let options = ParseOptions { search: false, ..ParseOptions::default() };
+        assert!(matches!(parse(&options), Ok(None)));
+
+        let options = ParseOptions { search: true, ..options };
+        assert!(matches!(parse(&options), Err(ErrorKind::UnsupportedTag(0))));
+
+        let options =
+            ParseOptions { unknown_tags: UnknownTagPolicy::Skip, ..options };
+        let options = ParseOptions {
+            unimplemented: UnimplementedPolicy::Reject,
+            ..options
+        };
+        assert!(matches!(parse(&options), Err(ErrorKind::Unimplemented(48))));
+
+        let options = ParseOptions {
+            unimplemented: UnimplementedPolicy::Skip,
+            ..options
+        };
+        assert_eq!(
+            parse(&options).unwrap().unwrap(),
+            [Tag::PrecisionTimeStamp, Tag::Checksum]
+        );
+
+        let options = ParseOptions {
+            unimplemented: UnimplementedPolicy::Keep,
+            ..options
+        };
+        assert_eq!(
+            parse(&options).unwrap().unwrap(),
+            [Tag::PrecisionTimeStamp, Tag::SecurityLocalSet, Tag::Checksum]
+        );
+
+        let options = ParseOptions { max_packet_size: 32, ..options };
+        assert!(matches!(parse(&options), Err(ErrorKind::PacketTooLarge(37))));
+    }
+
+    #[test]
+    fn max_depth() {
+        // A segment local set with a mission ID of 'A' and a segment local
+        // set of its own with a mission ID of 'B'.
+        let bytes = packet_from_value(vec![
+            0x64, 0x08, 0x03, 0x01, b'A', 0x64, 0x03, 0x03, 0x01, b'B',
+        ]);
+        let parse = |max_depth| {
+            let options = ParseOptions { max_depth, ..ParseOptions::default() };
+            KlvPacket::parse_with(&bytes, &options)
+                .map(|packet| packet.unwrap().0)
+        };
+
+        let packet = parse(3).unwrap();
+        let Some(KlvValue::Set(segments)) =
+            packet.get_ref(Tag::SegmentLocalSet).map(Klv::value)
+        else {
+            panic!("The segment local set wasn't decoded");
+        };
+        assert_eq!(segments[0].mission_id(), Some("A"));
+        assert!(matches!(
+            segments[0].get_ref(Tag::SegmentLocalSet).map(Klv::value),
+            Some(KlvValue::Set(inner)) if inner[0].mission_id() == Some("B")
+        ));
+
+        assert!(matches!(parse(2), Err(ErrorKind::TooDeep(2))));
+        assert!(matches!(parse(1), Err(ErrorKind::TooDeep(1))));
+    }
+
+    #[test]
+    fn invalid_lengths() {
+        // A two byte sensor latitude and a mission ID of 'ID'.
+        let bytes = packet_from_value(vec![
+            0x0D, 0x02, 0xFF, 0xFE, 0x03, 0x02, b'I', b'D',
+        ]);
+        let parse = |invalid_lengths| {
+            let options =
+                ParseOptions { invalid_lengths, ..ParseOptions::default() };
+            KlvPacket::parse_with(&bytes, &options)
+                .map(|packet| packet.unwrap().0)
+        };
+
+        assert!(matches!(
+            parse(LengthPolicy::Reject),
+            Err(ErrorKind::InvalidValueLength(13, 2))
+        ));
+
+        let packet = parse(LengthPolicy::Skip).unwrap();
+        assert_eq!(packet.get_ref(Tag::SensorLatitude), None);
+        assert_eq!(packet.mission_id(), Some("ID"));
+
+        let packet = parse(LengthPolicy::Keep).unwrap();
+        assert_eq!(
+            packet.get_ref(Tag::SensorLatitude).map(|field| field.value()),
+            Some(&KlvValue::Int32(-2))
+        );
+        assert_eq!(packet.mission_id(), Some("ID"));
+    }
+
+    #[test]
+    fn max_packet_size() {
+        // A set of 32 bytes whose first field runs past the end of the set.
+        let mut bytes = Vec::from(UAS_LOCAL_SET_UNIVERSAL_LABEL);
+        bytes.extend_from_slice(&[0x20, 0x03, 0x7F]);
+        bytes.extend_from_slice(&[0; 30]);
+
+        let options = ParseOptions::default();
+        assert!(matches!(
+            KlvPacket::parse_with(&bytes, &options),
+            Err(ErrorKind::InvalidLength)
+        ));
+        // The declared length is checked before the fields are read.
+        let options = ParseOptions { max_packet_size: 48, ..options };
+        assert!(matches!(
+            KlvPacket::parse_with(&bytes, &options),
+            Err(ErrorKind::PacketTooLarge(49))
+        ));
+    }
+
+    #[test]
+    fn malformed_value() {
+        // Mission ID that isn't valid UTF-8.
+        let bytes = packet_from_value(vec![0x03, 0x02, 0xC3, 0x28]);
+        assert!(matches!(
+            KlvPacket::from_bytes(&bytes),
+            Err(ErrorKind::MalformedValue(3))
+        ));
+    }
+
+    #[test_case(vec![0x05, 0x03, 0x01, 0x02, 0x03], 5, 3; "Uint16 too long")]
+    #[test_case(vec![0x02, 0x09, 0, 0, 0, 0, 0, 0, 0, 0, 1], 2, 9; "Uint64 too long")]
+    #[test_case(vec![0x0D, 0x02, 0x12, 0x34], 13, 2; "Two byte sensor latitude")]
+    #[test_case(vec![0x81, 0x08, 0x05, 0, 0, 0, 0, 1], 136, 5; "Leap seconds too long")]
+    fn invalid_value_length(value: Vec<u8>, tag: usize, length: usize) {
+        let bytes = packet_from_value(value);
+        assert!(matches!(
+            KlvPacket::from_bytes(&bytes),
+            Err(ErrorKind::InvalidValueLength(t, l)) if t == tag && l == length
+        ));
+    }
+
     #[test_case(&[0x71, 0xF1, 0x00], 113; "Short form")]
     #[test_case(&[0x81, 0xF1, 0x00], 241; "Long-Form: One byte")]
     #[test_case(&[0x83, 0xF1, 0xFF, 0xF1], 15859697; "Long-Form Three bytes")]
     fn get_ber_value(bytes: &[u8], correct_length: usize) {
         let mut test_bytes = Cursor::new(bytes.clone());
-        let length = KlvPacket::get_ber_value(&mut test_bytes);
+        let length = KlvPacket::get_ber_value(&mut test_bytes).unwrap();
         assert_eq!(length, correct_length, "Failed to BER")
     }
+
+    #[test_case(&[0x82, 0xF1]; "Truncated long form")]
+    #[test_case(&[0x89, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09]; "Too long")]
+    #[test_case(&[]; "Empty")]
+    fn get_ber_value_invalid(bytes: &[u8]) {
+        let mut buf = Cursor::new(bytes);
+        assert!(KlvPacket::get_ber_value(&mut buf).is_err());
+    }
+
+    /// Generate one field for every tag whose value can be encoded.
+    fn fields() -> impl Strategy<Value = Vec<Klv>> {
+        Tag::iter()
+            .filter(|tag| {
+                *tag != Tag::Checksum && ENCODABLE.contains(&tag.tag_type())
+            })
+            .map(|tag| {
+                value(tag.tag_type(), tag.info().length, tag.info().range)
+                    .prop_map(move |value| Klv::from_value(tag, value))
+            })
+            .collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn long_form_lengths() {
+        // Mission ID 'ID' with both lengths in the eight byte long form.
+        let mut bytes = Vec::from(UAS_LOCAL_SET_UNIVERSAL_LABEL);
+        bytes.extend_from_slice(&[0x88, 0, 0, 0, 0, 0, 0, 0, 16]);
+        bytes.extend_from_slice(&[0x03, 0x88, 0, 0, 0, 0, 0, 0, 0, 2]);
+        bytes.extend_from_slice(&[b'I', b'D', 0x01, 0x02]);
+        let checksum = UAS_CHECKSUM.calculate(&bytes).unwrap();
+        bytes.extend_from_slice(&checksum.to_be_bytes());
+
+        let packet = KlvPacket::from_bytes(&bytes).unwrap().unwrap();
+        assert_eq!(packet.mission_id(), Some("ID"));
+    }
+
+    #[test]
+    fn not_encodable() {
+        let packet = KlvPacket::from_fields(vec![Klv::from_value(
+            Tag::SecurityLocalSet,
+            KlvValue::Set(Vec::new()),
+        )]);
+        assert!(matches!(packet.to_bytes(), Err(ErrorKind::Unimplemented(48))));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn round_trip(fields in fields()) {
+            let packet = KlvPacket::from_fields(fields);
+            let bytes = packet.to_bytes().unwrap();
+            let decoded = KlvPacket::from_bytes(&bytes).unwrap().unwrap();
+
+            let decoded_fields: Vec<_> = decoded
+                .fields()
+                .filter(|field| field.tag() != Tag::Checksum)
+                .collect();
+            prop_assert_eq!(decoded_fields, packet.fields().collect::<Vec<_>>());
+            prop_assert_eq!(decoded.to_bytes().unwrap(), bytes);
+        }
+
+        #[test]
+        fn corruption_is_detected(
+            fields in fields(),
+            position in any::<Index>(),
+            byte in any::<u8>(),
+        ) {
+            let mut bytes = KlvPacket::from_fields(fields).to_bytes().unwrap();
+            // Anything after the universal label.
+            let position = 16 + position.index(bytes.len() - 16);
+            prop_assume!(bytes[position] != byte);
+            bytes[position] = byte;
+            prop_assert!(KlvPacket::from_bytes(&bytes).is_err());
+        }
+
+        #[test]
+        fn repair(fields in fields(), error in 1u16..) {
+            let valid = KlvPacket::from_fields(fields).to_bytes().unwrap();
+            let end = valid.len() - 2;
+            let checksum = u16::from_be_bytes([valid[end], valid[end + 1]]);
+
+            let mut bytes = valid.clone();
+            bytes[end..].copy_from_slice(&(checksum ^ error).to_be_bytes());
+            let (_, status) = KlvPacket::from_bytes_with_checksum(
+                &bytes,
+                ChecksumPolicy::Lenient,
+            )
+            .unwrap()
+            .unwrap();
+            prop_assert_eq!(
+                status,
+                ChecksumStatus::Mismatch {
+                    found: checksum ^ error,
+                    calculated: checksum
+                }
+            );
+
+            prop_assert_eq!(
+                KlvPacket::repair_checksum(&mut bytes).unwrap(),
+                Some(checksum)
+            );
+            prop_assert_eq!(bytes, valid);
+        }
+    }
 }
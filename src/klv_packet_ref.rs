@@ -0,0 +1,325 @@
+//! A view of a UAS LS KLV packet that borrows the buffer it was parsed from.
+//!
+//! Parsing a [`KlvPacketRef`] checks the layout and checksum of the packet
+//! without allocating. Values are only decoded when they are accessed, so
+//! callers that need a handful of tags from each packet don't pay for
+//! decoding the rest.
+#[cfg(feature = "tracing")]
+use tracing::trace;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::ErrorKind;
+use crate::klv::Klv;
+use crate::klv_packet::KlvPacket;
+use crate::klv_packet::UAS_CHECKSUM;
+use crate::klv_packet::UAS_LENGTH_ENCODING;
+use crate::klv_packet::UAS_LOCAL_SET_UNIVERSAL_LABEL;
+use crate::klv_packet::UAS_TAG_ENCODING;
+use crate::klv_value::KlvValue;
+use crate::klv_value::KlvValueType;
+use crate::local_set::ChecksumPolicy;
+use crate::local_set::ChecksumStatus;
+use crate::local_set::Frame;
+use crate::options::DuplicatePolicy;
+use crate::options::LengthPolicy;
+use crate::options::ParseOptions;
+use crate::options::UnimplementedPolicy;
+use crate::options::UnknownTagPolicy;
+use crate::tag::Tag;
+
+/// A single field of a [`KlvPacketRef`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KlvFieldRef<'a> {
+    id: usize,
+    raw: &'a [u8],
+}
+
+impl<'a> KlvFieldRef<'a> {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn tag(&self) -> Tag {
+        Tag::from(self.id)
+    }
+
+    /// The undecoded bytes of the value.
+    pub fn raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Decode the value of the field.
+    pub fn value(&self) -> Result<KlvValue, ErrorKind> {
+        KlvValue::from_bytes(self.tag(), self.raw)
+    }
+
+    /// Decode the field into an owned [`Klv`].
+    pub fn to_klv(&self) -> Result<Klv, ErrorKind> {
+        Klv::from_raw(self.id, self.raw)
+    }
+
+    /// Decode the field of a set `depth` sets deep into an owned [`Klv`],
+    /// handling a value whose length isn't allowed for its tag according to
+    /// `options`. Returns `Ok(None)` if the field is skipped.
+    fn decode_with(
+        &self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<Option<Klv>, ErrorKind> {
+        if self.tag().tag_type() == KlvValueType::Set {
+            // Local sets inside the set are one level deeper than it.
+            if depth >= options.max_depth {
+                return Err(ErrorKind::TooDeep(options.max_depth));
+            }
+            if is_nested_packet(self.tag()) {
+                let packet = decode_set(self.raw, options, depth + 1)?;
+                return Ok(Some(Klv::from_value(
+                    self.tag(),
+                    KlvValue::Set(vec![packet]),
+                )));
+            }
+        }
+
+        match (self.to_klv(), options.invalid_lengths) {
+            (Err(ErrorKind::InvalidValueLength(..)), LengthPolicy::Skip) => {
+                Ok(None)
+            }
+            (Err(ErrorKind::InvalidValueLength(..)), LengthPolicy::Keep) => {
+                let value =
+                    KlvValue::from_bytes_any_length(self.tag(), self.raw)?;
+                Ok(Some(Klv::from_value(self.tag(), value)))
+            }
+            (klv, _) => klv.map(Some),
+        }
+    }
+}
+
+/// A UAS LS KLV packet borrowed from the buffer it was parsed from.
+#[derive(Clone, Copy, Debug)]
+pub struct KlvPacketRef<'a> {
+    bytes: &'a [u8],
+    frame: Frame<'a>,
+    checksum: ChecksumStatus,
+}
+
+impl<'a> KlvPacketRef<'a> {
+    /// Parse the bytes into a borrowed KLV packet.
+    ///
+    /// This fails in the same cases as [`KlvPacket::from_bytes`] except for
+    /// values that can't be decoded, which are only reported when the value
+    /// is accessed.
+    pub fn from_bytes(
+        bytes: &'a [u8],
+    ) -> Result<Option<KlvPacketRef<'a>>, ErrorKind> {
+        KlvPacketRef::from_bytes_with_checksum(bytes, ChecksumPolicy::Strict)
+    }
+
+    /// Parse the bytes into a borrowed KLV packet, handling a missing or
+    /// wrong checksum according to `policy`.
+    pub fn from_bytes_with_checksum(
+        bytes: &'a [u8],
+        policy: ChecksumPolicy,
+    ) -> Result<Option<KlvPacketRef<'a>>, ErrorKind> {
+        KlvPacketRef::parse_with(
+            bytes,
+            &ParseOptions { checksum: policy, ..ParseOptions::default() },
+        )
+    }
+
+    /// Parse the bytes into a borrowed KLV packet using `options`.
+    ///
+    /// Values are not decoded, so [`ParseOptions::unimplemented`] and
+    /// [`ParseOptions::max_depth`] only apply in [`Self::to_packet_with`].
+    pub fn parse_with(
+        bytes: &'a [u8],
+        options: &ParseOptions,
+    ) -> Result<Option<KlvPacketRef<'a>>, ErrorKind> {
+        let Some(frame) = Frame::parse(
+            bytes,
+            &UAS_LOCAL_SET_UNIVERSAL_LABEL,
+            UAS_LENGTH_ENCODING,
+            UAS_TAG_ENCODING,
+            UAS_LENGTH_ENCODING,
+            options.search,
+            options.max_packet_size,
+        )?
+        else {
+            return Ok(None);
+        };
+
+        if options.unknown_tags == UnknownTagPolicy::Reject {
+            for (tag, value) in frame.fields() {
+                if !value.is_empty() && !is_known(tag) {
+                    return Err(ErrorKind::UnsupportedTag(tag));
+                }
+            }
+        }
+
+        let checksum = options
+            .checksum
+            .check(frame.checksum_status(bytes, UAS_CHECKSUM))?;
+
+        Ok(Some(KlvPacketRef { bytes, frame, checksum }))
+    }
+
+    /// Whether the checksum of the packet matched its contents.
+    pub fn checksum_status(&self) -> ChecksumStatus {
+        self.checksum
+    }
+
+    /// The bytes of the packet, from the start of the universal key to the
+    /// end of the checksum.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        &self.bytes[self.frame.start..self.frame.end]
+    }
+
+    /// Every field with a value, in the order they appear in the packet.
+    pub fn fields(&self) -> impl Iterator<Item = KlvFieldRef<'a>> + 'a {
+        self.frame
+            .fields()
+            .filter(|(_, raw)| !raw.is_empty())
+            .map(|(id, raw)| KlvFieldRef { id, raw })
+    }
+
+    pub fn get_id(&self, tag: usize) -> Option<KlvFieldRef<'a>> {
+        self.fields().find(|field| field.id == tag)
+    }
+
+    pub fn get(&self, tag: Tag) -> Option<KlvFieldRef<'a>> {
+        self.get_id(tag.into())
+    }
+
+    /// Every field for `tag`, in the order they appear in the packet.
+    pub fn get_all(&self, tag: Tag) -> impl Iterator<Item = KlvFieldRef<'a>> {
+        self.fields().filter(move |field| field.id == tag.id())
+    }
+
+    /// Return the checksum of this UAS LS KLV packet
+    pub fn checksum(&self) -> Option<u16> {
+        Some(u16::from_be_bytes(self.get(Tag::Checksum)?.raw.try_into().ok()?))
+    }
+
+    /// Return the precision time stamp of the UAS LS KLV packet
+    pub fn precision_time_stamp(&self) -> Option<u64> {
+        Some(u64::from_be_bytes(
+            self.get(Tag::PrecisionTimeStamp)?.raw.try_into().ok()?,
+        ))
+    }
+
+    /// Decode every field into an owned [`KlvPacket`].
+    pub fn to_packet(&self) -> Result<KlvPacket, ErrorKind> {
+        self.to_packet_with(&ParseOptions::default())
+    }
+
+    /// Decode every field into an owned [`KlvPacket`], handling unknown tags,
+    /// values that can't be decoded yet and values of the wrong length
+    /// according to `options`.
+    pub fn to_packet_with(
+        &self,
+        options: &ParseOptions,
+    ) -> Result<KlvPacket, ErrorKind> {
+        decode_fields(self.fields(), options, 1)
+    }
+}
+
+/// Whether `tag` is a local set of UAS LS fields, which is decoded as a
+/// packet nested inside the packet holding it.
+pub(crate) fn is_nested_packet(tag: Tag) -> bool {
+    matches!(tag, Tag::SegmentLocalSet | Tag::AmendLocalSet)
+}
+
+/// Decode the value of a Segment or Amend local set that is `depth` sets
+/// deep into a packet.
+pub(crate) fn decode_set(
+    bytes: &[u8],
+    options: &ParseOptions,
+    depth: usize,
+) -> Result<KlvPacket, ErrorKind> {
+    let frame = Frame::nested(bytes, UAS_TAG_ENCODING, UAS_LENGTH_ENCODING)?;
+    let fields = frame
+        .fields()
+        .filter(|(_, raw)| !raw.is_empty())
+        .map(|(id, raw)| KlvFieldRef { id, raw });
+    decode_fields(fields, options, depth)
+}
+
+/// Decode the fields of a set that is `depth` sets deep into a packet.
+fn decode_fields<'a>(
+    fields: impl Iterator<Item = KlvFieldRef<'a>>,
+    options: &ParseOptions,
+    depth: usize,
+) -> Result<KlvPacket, ErrorKind> {
+    let mut decoded = Vec::new();
+    for field in fields {
+        if !is_known(field.id) {
+            match options.unknown_tags {
+                UnknownTagPolicy::Reject => {
+                    return Err(ErrorKind::UnsupportedTag(field.id));
+                }
+                UnknownTagPolicy::Skip => continue,
+            }
+        }
+
+        let Some(klv) = field.decode_with(options, depth)? else {
+            continue;
+        };
+        if matches!(klv.value(), KlvValue::Unimplemented) {
+            match options.unimplemented {
+                UnimplementedPolicy::Reject => {
+                    return Err(ErrorKind::Unimplemented(field.id));
+                }
+                UnimplementedPolicy::Keep => {}
+                UnimplementedPolicy::Skip => continue,
+            }
+        }
+        #[cfg(feature = "tracing")]
+        trace!(
+            "Added tag to KLV packet: [{}]",
+            Into::<&'static str>::into(klv.tag())
+        );
+        decoded.push(klv);
+    }
+
+    let packet = KlvPacket::from_fields_with(decoded, options.duplicates);
+    if options.duplicates == DuplicatePolicy::Reject {
+        if let Some(duplicate) = packet.duplicates().first() {
+            return Err(ErrorKind::DuplicateTag(duplicate.tag.id()));
+        }
+    }
+
+    Ok(packet)
+}
+
+/// Whether `tag` is a current tag of MISB ST 0601.
+fn is_known(tag: usize) -> bool {
+    // If the tag is larger than the known max tag ID then we know it's not
+    // supported
+    tag <= Tag::COUNT
+        && !matches!(Tag::from(tag), Tag::Unknown | Tag::Deprecated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KlvPacketRef;
+    use crate::klv_packet::tests::packet_from_value;
+    use crate::klv_value::KlvValue;
+    use crate::tag::Tag;
+
+    #[test]
+    fn borrowed() {
+        let bytes = packet_from_value(vec![0x03, 0x02, b'I', b'D']);
+        let packet = KlvPacketRef::from_bytes(&bytes).unwrap().unwrap();
+
+        assert_eq!(packet.as_bytes(), &bytes[..]);
+        assert_eq!(packet.precision_time_stamp(), Some(0x0011_2233_4455_6677));
+        let field = packet.get(Tag::MissionID).unwrap();
+        assert_eq!(field.raw(), b"ID");
+        assert!(
+            matches!(field.value().unwrap(), KlvValue::Utf8(id) if &*id == "ID")
+        );
+        assert_eq!(packet.fields().count(), 3);
+        assert_eq!(packet.to_packet().unwrap().mission_id(), Some("ID"));
+    }
+}
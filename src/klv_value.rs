@@ -1,22 +1,33 @@
 //! Information from this page was gathered from page 32 of the MISB ST 0601.19
 //! document that was published 2023-March-02.
-use std::sync::Arc;
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Display;
+use core::fmt::Formatter;
 
 use bitvec::field::BitField;
 use bitvec::order::Msb0;
+use bitvec::slice::BitSlice;
 use bitvec::view::BitView;
 #[cfg(feature = "tracing")]
 use tracing::warn;
 use strum_macros::EnumDiscriminants;
 
 use crate::ErrorKind;
+use crate::imap;
 use crate::klv_packet::KlvPacket;
+use crate::klv_packet_ref;
+use crate::options::ParseOptions;
+use crate::sdcc::Sdcc;
+use crate::tag::Length;
 use crate::tag::Tag;
 
 /// The value types that are supported to be stored in a UAS Datalink KLV
 /// packet. The first value is always the tag number. The second value is the
 /// value.
-#[derive(Clone, Debug, EnumDiscriminants)]
+#[derive(Clone, Debug, PartialEq, EnumDiscriminants)]
 #[strum_discriminants(vis(pub))]
 #[strum_discriminants(name(KlvValueType))]
 pub enum KlvValue {
@@ -65,135 +76,457 @@ pub enum KlvValue {
     Byte(Box<[u8]>),
     /// Defined length pack
     ///
-    /// TODO: Look into what this enum's variant should be
-    /// NOTE: This is not supported yet.
-    DLP,
+    /// Holds the raw bytes of the pack, like [`KlvValue::VLP`].
+    DLP(Box<[u8]>),
     /// Variable length pack
     ///
-    /// TODO: Look into what this enum's variant should be
-    /// NOTE: This is not supported yet.
-    VLP,
+    /// Holds the raw bytes of the pack. Packs with a known layout are decoded
+    /// by the typed accessors on [`KlvPacket`].
+    VLP(Box<[u8]>),
     /// Floating length pack
     ///
-    /// TODO: Look into what this enum's variant should be
-    /// NOTE: This is not supported yet.
-    FLP,
+    /// The only floating length pack in the UAS LS is the Standard Deviation
+    /// and Cross Correlation pack (see MISB ST 1010).
+    FLP(Sdcc),
     /// Local Set
+    ///
+    /// Segment and Amend local sets hold a single packet of UAS LS fields.
     Set(Vec<KlvPacket>),
     /// String of characters following the utf8 standard
     Utf8(Arc<str>),
 }
 
 impl KlvValue {
+    /// Decode the value of a UAS LS tag.
+    ///
+    /// The length of the value must match the length given by [`Tag::info`],
+    /// so fixed width values such as [`Tag::SensorLatitude`] are rejected
+    /// when they are shorter or longer than the standard requires.
     pub fn from_bytes(tag: Tag, bytes: &[u8]) -> Result<KlvValue, ErrorKind> {
-        let t = tag.tag_type();
-        let value = match t {
-            KlvValueType::Int => Self::int(bytes),
-            KlvValueType::Int8 => Self::int8(bytes),
-            KlvValueType::Int16 => Self::int16(bytes),
-            KlvValueType::Int32 => Self::int32(bytes),
-            KlvValueType::Uint => Self::uint(bytes),
-            KlvValueType::Uint8 => Self::uint8(bytes),
-            KlvValueType::Uint16 => Self::uint16(bytes),
-            KlvValueType::Uint32 => Self::uint32(bytes),
-            KlvValueType::Uint64 => Self::uint64(bytes),
-            KlvValueType::Utf8 => Self::utf8(bytes),
-            KlvValueType::IMAPB => Self::imapb(bytes),
-            KlvValueType::Set => Self::set(tag, bytes),
+        let valid = match tag.info().length {
+            Length::Fixed(length) => bytes.len() == length,
+            Length::Max(length) => bytes.len() <= length,
+            Length::Variable => true,
+        };
+        if !valid {
+            return Err(ErrorKind::InvalidValueLength(tag.id(), bytes.len()));
+        }
+        Self::from_bytes_any_length(tag, bytes)
+    }
+
+    /// Decode the value of a UAS LS tag whatever the length [`Tag::info`]
+    /// gives for it, as long as the value fits in the type of the tag.
+    pub(crate) fn from_bytes_any_length(
+        tag: Tag,
+        bytes: &[u8],
+    ) -> Result<KlvValue, ErrorKind> {
+        if tag.tag_type() == KlvValueType::IMAPB {
+            return Self::imapb(tag.id(), tag.info().range, bytes);
+        }
+        Self::from_type(tag.tag_type(), tag.id(), bytes)
+    }
+
+    /// Decode `bytes` as a value of the given type. `tag` is the ID of the
+    /// tag the value belongs to.
+    ///
+    /// Integers may be shorter than their type, in which case signed values
+    /// are sign extended from their first bit.
+    pub fn from_type(
+        value_type: KlvValueType,
+        tag: usize,
+        bytes: &[u8],
+    ) -> Result<KlvValue, ErrorKind> {
+        let value = match value_type {
+            KlvValueType::Int => {
+                KlvValue::Int(Self::bits(tag, bytes, 8)?.load_be())
+            }
+            KlvValueType::Int8 => {
+                KlvValue::Int8(Self::bits(tag, bytes, 1)?.load_be())
+            }
+            KlvValueType::Int16 => {
+                KlvValue::Int16(Self::bits(tag, bytes, 2)?.load_be())
+            }
+            KlvValueType::Int32 => {
+                KlvValue::Int32(Self::bits(tag, bytes, 4)?.load_be())
+            }
+            KlvValueType::Uint => {
+                KlvValue::Uint(Self::bits(tag, bytes, 8)?.load_be())
+            }
+            KlvValueType::Uint8 => {
+                KlvValue::Uint8(Self::bits(tag, bytes, 1)?.load_be())
+            }
+            KlvValueType::Uint16 => {
+                KlvValue::Uint16(Self::bits(tag, bytes, 2)?.load_be())
+            }
+            KlvValueType::Uint32 => {
+                KlvValue::Uint32(Self::bits(tag, bytes, 4)?.load_be())
+            }
+            KlvValueType::Uint64 => {
+                KlvValue::Uint64(Self::bits(tag, bytes, 8)?.load_be())
+            }
+            KlvValueType::Utf8 => Self::utf8(tag, bytes)?,
+            KlvValueType::IMAPB => Self::imapb(tag, None, bytes)?,
+            KlvValueType::Set => Self::set(Tag::from(tag), bytes)?,
             KlvValueType::Byte => Self::byte(bytes),
             KlvValueType::DLP => Self::dlp(bytes),
             KlvValueType::VLP => Self::vlp(bytes),
-            KlvValueType::FLP => Self::flp(bytes),
-            _ => return Err(ErrorKind::UnsupportedTag(tag.into())),
+            KlvValueType::FLP => Self::flp(bytes)?,
+            _ => return Err(ErrorKind::UnsupportedTag(tag)),
         };
 
         Ok(value)
     }
 
-    fn int(bytes: &[u8]) -> KlvValue {
-        KlvValue::Int(bytes.view_bits::<Msb0>().load_be())
+    /// Load a big endian integer that is at most `size` bytes long.
+    ///
+    /// Empty values and values longer than `size` are rejected with
+    /// [`ErrorKind::InvalidValueLength`] rather than truncated.
+    fn bits(
+        tag: usize,
+        bytes: &[u8],
+        size: usize,
+    ) -> Result<&BitSlice<u8, Msb0>, ErrorKind> {
+        if bytes.is_empty() || bytes.len() > size {
+            return Err(ErrorKind::InvalidValueLength(tag, bytes.len()));
+        }
+        Ok(bytes.view_bits::<Msb0>())
+    }
+
+    fn utf8(tag: usize, bytes: &[u8]) -> Result<KlvValue, ErrorKind> {
+        core::str::from_utf8(bytes)
+            .map(|value| KlvValue::Utf8(value.into()))
+            .map_err(|_| ErrorKind::MalformedValue(tag))
     }
 
-    fn int8(bytes: &[u8]) -> KlvValue {
-        KlvValue::Int8(bytes.view_bits::<Msb0>().load_be())
+    /// Decode an IMAPB value onto `range`. The range isn't part of the value
+    /// type, so values without one can't be decoded.
+    fn imapb(
+        tag: usize,
+        range: Option<(f64, f64)>,
+        bytes: &[u8],
+    ) -> Result<KlvValue, ErrorKind> {
+        let Some((min, max)) = range else {
+            return Ok(Self::klv_unimplemented("IMAPB"));
+        };
+        imap::imapb(min, max, bytes)
+            .map(KlvValue::IMAPB)
+            .ok_or(ErrorKind::InvalidValueLength(tag, bytes.len()))
     }
 
-    fn int16(bytes: &[u8]) -> KlvValue {
-        KlvValue::Int16(bytes.view_bits::<Msb0>().load_be())
+    /// Segment and Amend local sets hold UAS LS fields, so they are decoded
+    /// as a packet nested in the packet holding them. Other local sets can't
+    /// be decoded yet.
+    fn set(tag: Tag, bytes: &[u8]) -> Result<KlvValue, ErrorKind> {
+        if !klv_packet_ref::is_nested_packet(tag) {
+            return Ok(Self::klv_unimplemented("Set"));
+        }
+        let options = ParseOptions::default();
+        let packet = klv_packet_ref::decode_set(bytes, &options, 2)?;
+        Ok(KlvValue::Set(vec![packet]))
     }
 
-    fn int32(bytes: &[u8]) -> KlvValue {
-        KlvValue::Int32(bytes.view_bits::<Msb0>().load_be())
+    fn byte(bytes: &[u8]) -> KlvValue {
+        KlvValue::Byte(bytes.into())
     }
 
-    fn uint(bytes: &[u8]) -> KlvValue {
-        KlvValue::Uint(bytes.view_bits::<Msb0>().load_be())
+    fn dlp(bytes: &[u8]) -> KlvValue {
+        KlvValue::DLP(bytes.into())
     }
 
-    fn uint8(bytes: &[u8]) -> KlvValue {
-        KlvValue::Uint8(bytes.view_bits::<Msb0>().load_be())
+    fn vlp(bytes: &[u8]) -> KlvValue {
+        KlvValue::VLP(bytes.into())
     }
 
-    fn uint16(bytes: &[u8]) -> KlvValue {
-        KlvValue::Uint16(bytes.view_bits::<Msb0>().load_be())
+    fn flp(bytes: &[u8]) -> Result<KlvValue, ErrorKind> {
+        Ok(KlvValue::FLP(Sdcc::from_bytes(bytes)?))
     }
 
-    fn uint32(bytes: &[u8]) -> KlvValue {
-        KlvValue::Uint32(bytes.view_bits::<Msb0>().load_be())
+    /// Encode the value into the bytes of a KLV field.
+    ///
+    /// Fixed width integers are encoded at their full width and variable
+    /// length integers in the fewest bytes that hold them. Returns `None` for
+    /// values that can't be encoded yet, and for IMAPB values which need the
+    /// range of their tag (see [`Klv::to_bytes`]).
+    ///
+    /// [`Klv::to_bytes`]: crate::klv::Klv::to_bytes
+    pub fn to_bytes(&self) -> Option<Vec<u8>> {
+        let bytes = match self {
+            KlvValue::Int(value) => {
+                // Drop leading bytes that only repeat the sign bit.
+                let bytes = value.to_be_bytes();
+                let skip = (0..7)
+                    .take_while(|idx| {
+                        let sign =
+                            if bytes[idx + 1] & 0x80 != 0 { 0xFF } else { 0 };
+                        bytes[*idx] == sign
+                    })
+                    .count();
+                bytes[skip..].to_vec()
+            }
+            KlvValue::Int8(value) => value.to_be_bytes().to_vec(),
+            KlvValue::Int16(value) => value.to_be_bytes().to_vec(),
+            KlvValue::Int32(value) => value.to_be_bytes().to_vec(),
+            KlvValue::Uint(value) => {
+                let bytes = value.to_be_bytes();
+                let skip = (value.leading_zeros() as usize / 8).min(7);
+                bytes[skip..].to_vec()
+            }
+            KlvValue::Uint8(value) => value.to_be_bytes().to_vec(),
+            KlvValue::Uint16(value) => value.to_be_bytes().to_vec(),
+            KlvValue::Uint32(value) => value.to_be_bytes().to_vec(),
+            KlvValue::Uint64(value) => value.to_be_bytes().to_vec(),
+            KlvValue::Byte(bytes)
+            | KlvValue::VLP(bytes)
+            | KlvValue::DLP(bytes) => bytes.to_vec(),
+            KlvValue::FLP(sdcc) => sdcc.to_bytes(),
+            KlvValue::Utf8(value) => value.as_bytes().to_vec(),
+            KlvValue::Unknown
+            | KlvValue::Deprecated
+            | KlvValue::Unimplemented
+            | KlvValue::IMAPB(_)
+            | KlvValue::Set(_) => return None,
+        };
+        Some(bytes)
     }
 
-    fn uint64(bytes: &[u8]) -> KlvValue {
-        KlvValue::Uint64(bytes.view_bits::<Msb0>().load_be())
+    fn klv_unimplemented(_tag_type: &str) -> KlvValue {
+        #[cfg(feature = "tracing")]
+        warn!("Converting KLV bytes to {} is not yet supported", _tag_type);
+        KlvValue::Unimplemented
     }
+}
 
-    fn utf8(bytes: &[u8]) -> KlvValue {
-        KlvValue::Utf8(
-            std::str::from_utf8(bytes)
-                .unwrap_or_else(|_| {
-                    panic!(
-                        "Cannot create UTF8 string from bytes {:02X?}",
-                        bytes
-                    )
-                })
-                .into(),
-        )
+impl Display for KlvValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            KlvValue::Unknown => write!(f, "Unknown"),
+            KlvValue::Deprecated => write!(f, "Deprecated"),
+            KlvValue::Unimplemented | KlvValue::Set(_) => {
+                write!(f, "Unimplemented")
+            }
+            KlvValue::Int(value) => write!(f, "{}", value),
+            KlvValue::Int8(value) => write!(f, "{}", value),
+            KlvValue::Int16(value) => write!(f, "{}", value),
+            KlvValue::Int32(value) => write!(f, "{}", value),
+            KlvValue::Uint(value) | KlvValue::Uint64(value) => {
+                write!(f, "{}", value)
+            }
+            KlvValue::Uint8(value) => write!(f, "{}", value),
+            KlvValue::Uint16(value) => write!(f, "{}", value),
+            KlvValue::Uint32(value) => write!(f, "{}", value),
+            KlvValue::IMAPB(value) => write!(f, "{}", value),
+            KlvValue::Byte(bytes)
+            | KlvValue::VLP(bytes)
+            | KlvValue::DLP(bytes) => {
+                for (idx, byte) in bytes.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{:02X}", byte)?;
+                }
+                Ok(())
+            }
+            KlvValue::FLP(sdcc) => {
+                write!(f, "SDCC of {} tags", sdcc.tags().len())
+            }
+            KlvValue::Utf8(value) => write!(f, "{}", value),
+        }
     }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+    use test_case::test_case;
+
+    use super::KlvValue;
+    use super::KlvValueType;
+    use crate::ErrorKind;
+    use crate::klv::Klv;
+    use crate::sdcc::tests::sdcc;
+    use crate::tag::Length;
+    use crate::tag::Tag;
+
+    /// Value types that can be encoded with [`KlvValue::to_bytes`].
+    pub(crate) const ENCODABLE: [KlvValueType; 15] = [
+        KlvValueType::Int,
+        KlvValueType::Int8,
+        KlvValueType::Int16,
+        KlvValueType::Int32,
+        KlvValueType::Uint,
+        KlvValueType::Uint8,
+        KlvValueType::Uint16,
+        KlvValueType::Uint32,
+        KlvValueType::Uint64,
+        KlvValueType::Byte,
+        KlvValueType::DLP,
+        KlvValueType::VLP,
+        KlvValueType::FLP,
+        KlvValueType::Utf8,
+        KlvValueType::IMAPB,
+    ];
 
-    fn imapb(bytes: &[u8]) -> KlvValue {
-        Self::klv_unimplemented("IMAPB")
+    /// Generate values of an encodable type whose encoding fits in `length`.
+    ///
+    /// IMAPB values are generated within `range` in steps of 1/256, which
+    /// every tag's range encodes exactly in 8 bytes.
+    pub(crate) fn value(
+        value_type: KlvValueType,
+        length: Length,
+        range: Option<(f64, f64)>,
+    ) -> BoxedStrategy<KlvValue> {
+        let (min, max) = match length {
+            Length::Fixed(size) => (size, size),
+            Length::Max(size) => (1, size),
+            Length::Variable => (1, 64),
+        };
+        let bits = 8 * max.min(8) as u32;
+        match value_type {
+            KlvValueType::Int => {
+                let bound = i64::MAX >> (64 - bits);
+                (-bound - 1..=bound).prop_map(KlvValue::Int).boxed()
+            }
+            KlvValueType::Int8 => any::<i8>().prop_map(KlvValue::Int8).boxed(),
+            KlvValueType::Int16 => {
+                any::<i16>().prop_map(KlvValue::Int16).boxed()
+            }
+            KlvValueType::Int32 => {
+                any::<i32>().prop_map(KlvValue::Int32).boxed()
+            }
+            KlvValueType::Uint => {
+                let bound = u64::MAX >> (64 - bits);
+                (0..=bound).prop_map(KlvValue::Uint).boxed()
+            }
+            KlvValueType::Uint8 => {
+                any::<u8>().prop_map(KlvValue::Uint8).boxed()
+            }
+            KlvValueType::Uint16 => {
+                any::<u16>().prop_map(KlvValue::Uint16).boxed()
+            }
+            KlvValueType::Uint32 => {
+                any::<u32>().prop_map(KlvValue::Uint32).boxed()
+            }
+            KlvValueType::Uint64 => {
+                any::<u64>().prop_map(KlvValue::Uint64).boxed()
+            }
+            KlvValueType::Byte => vec(any::<u8>(), min..=max)
+                .prop_map(|bytes| KlvValue::Byte(bytes.into()))
+                .boxed(),
+            KlvValueType::DLP => vec(any::<u8>(), min..=max)
+                .prop_map(|bytes| KlvValue::DLP(bytes.into()))
+                .boxed(),
+            KlvValueType::VLP => vec(any::<u8>(), min..=max)
+                .prop_map(|bytes| KlvValue::VLP(bytes.into()))
+                .boxed(),
+            KlvValueType::FLP => sdcc().prop_map(KlvValue::FLP).boxed(),
+            KlvValueType::Utf8 => vec(any::<char>(), min..=max)
+                .prop_map(move |chars| {
+                    let mut value: String = chars.into_iter().collect();
+                    while value.len() > max {
+                        value.pop();
+                    }
+                    KlvValue::Utf8(value.into())
+                })
+                .boxed(),
+            KlvValueType::IMAPB => {
+                let (low, high) = range.expect("IMAPB values need a range");
+                (0..((high - low) * 256.0) as u64)
+                    .prop_map(move |step| {
+                        KlvValue::IMAPB(low + step as f64 / 256.0)
+                    })
+                    .boxed()
+            }
+            _ => panic!("{:?} values can't be encoded", value_type),
+        }
     }
 
-    fn set(tag: Tag, bytes: &[u8]) -> KlvValue {
-        Self::klv_unimplemented("Set")
+    #[test_case(KlvValueType::Int, &[0x7F], 127; "Int one byte positive")]
+    #[test_case(KlvValueType::Int, &[0xFF], -1; "Int one byte negative")]
+    #[test_case(KlvValueType::Int, &[0xFF, 0x7F], -129; "Int two bytes")]
+    #[test_case(KlvValueType::Int, &[0x80, 0x00, 0x00], -8_388_608; "Int three bytes")]
+    #[test_case(KlvValueType::Int, &[0x00, 0x80, 0x00, 0x00], 8_388_608; "Int leading zero")]
+    #[test_case(KlvValueType::Int, &[0x80, 0, 0, 0, 0, 0, 0, 0], i64::MIN; "Int eight bytes")]
+    #[test_case(KlvValueType::Int8, &[0x80], -128; "Int8")]
+    #[test_case(KlvValueType::Int16, &[0xFE], -2; "Short Int16")]
+    #[test_case(KlvValueType::Int32, &[0xFF, 0xFE], -2; "Short Int32")]
+    #[test_case(KlvValueType::Int32, &[0x00, 0xFE], 254; "Short positive Int32")]
+    fn sign_extension(value_type: KlvValueType, bytes: &[u8], value: i64) {
+        let decoded = match KlvValue::from_type(value_type, 0, bytes).unwrap() {
+            KlvValue::Int(value) => value,
+            KlvValue::Int8(value) => value.into(),
+            KlvValue::Int16(value) => value.into(),
+            KlvValue::Int32(value) => value.into(),
+            value => panic!("{:?} isn't a signed integer", value),
+        };
+        assert_eq!(decoded, value);
     }
 
-    fn byte(bytes: &[u8]) -> KlvValue {
-        Self::klv_unimplemented("Byte")
+    #[test_case(KlvValueType::Int, &[0; 9]; "Int")]
+    #[test_case(KlvValueType::Uint, &[0; 9]; "Uint")]
+    #[test_case(KlvValueType::Int8, &[0; 2]; "Int8")]
+    #[test_case(KlvValueType::Uint32, &[0; 5]; "Uint32")]
+    #[test_case(KlvValueType::Int16, &[]; "Empty")]
+    fn too_long(value_type: KlvValueType, bytes: &[u8]) {
+        assert!(matches!(
+            KlvValue::from_type(value_type, 7, bytes),
+            Err(ErrorKind::InvalidValueLength(7, length)) if length == bytes.len()
+        ));
     }
 
-    fn dlp(bytes: &[u8]) -> KlvValue {
-        Self::klv_unimplemented("DLP")
+    #[test_case(Tag::SensorLatitude, &[0x12, 0x34]; "Short fixed width")]
+    #[test_case(Tag::PlatformHeadingAngle, &[0x12, 0x34, 0x56]; "Long fixed width")]
+    #[test_case(Tag::PlatformTailNumber, &[b'A'; 128]; "Long string")]
+    #[test_case(Tag::LeapSeconds, &[0, 0, 0, 0, 0x12]; "Long integer")]
+    fn tag_length(tag: Tag, bytes: &[u8]) {
+        assert!(matches!(
+            KlvValue::from_bytes(tag, bytes),
+            Err(ErrorKind::InvalidValueLength(id, length))
+                if id == tag.id() && length == bytes.len()
+        ));
     }
 
-    fn vlp(bytes: &[u8]) -> KlvValue {
-        Self::klv_unimplemented("VLP")
+    #[test]
+    fn int_encoding() {
+        let encode = |value| KlvValue::Int(value).to_bytes().unwrap();
+        assert_eq!(encode(0), [0x00]);
+        assert_eq!(encode(127), [0x7F]);
+        assert_eq!(encode(128), [0x00, 0x80]);
+        assert_eq!(encode(-128), [0x80]);
+        assert_eq!(encode(-129), [0xFF, 0x7F]);
+        assert_eq!(encode(i64::MIN), i64::MIN.to_be_bytes());
+        assert_eq!(KlvValue::Uint(0).to_bytes().unwrap(), [0x00]);
+        assert_eq!(KlvValue::Uint(256).to_bytes().unwrap(), [0x01, 0x00]);
     }
 
-    fn flp(bytes: &[u8]) -> KlvValue {
-        Self::klv_unimplemented("FLP")
+    #[test]
+    fn not_encodable() {
+        assert_eq!(KlvValue::Unknown.to_bytes(), None);
+        assert_eq!(KlvValue::Set(Vec::new()).to_bytes(), None);
     }
 
-    fn klv_unimplemented(tag_type: &str) -> KlvValue {
-        #[cfg(not(feature = "ignore_incomplete"))]
-        {
-            todo!("Implement converting KLV bytes to {}", tag_type);
-        }
-        #[cfg(feature = "ignore_incomplete")]
-        {
-            #[cfg(feature = "tracing")]
-            warn!("Converting KLV bytes to {} is not yet supported", tag_type);
-            KlvValue::Unimplemented
+    proptest! {
+        #[test]
+        fn round_trip(
+            (value_type, value) in select(&ENCODABLE[..]).prop_flat_map(
+                |value_type| {
+                    let range = Tag::AltitudeAgl.info().range;
+                    let value = value(value_type, Length::Variable, range);
+                    (Just(value_type), value)
+                }
+            )
+        ) {
+            // IMAPB values are encoded onto the range of their tag.
+            let field = Klv::from_value(Tag::AltitudeAgl, value.clone());
+            let bytes = field.to_bytes().unwrap();
+            let decoded = match value_type {
+                KlvValueType::IMAPB => {
+                    KlvValue::from_bytes(Tag::AltitudeAgl, &bytes)
+                }
+                _ => KlvValue::from_type(value_type, 0, &bytes),
+            };
+            prop_assert_eq!(decoded.unwrap(), value);
         }
     }
 }
-
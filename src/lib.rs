@@ -1,12 +1,34 @@
 #![forbid(unsafe_code)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 // Use these checks when closer to complete. They're a bit too strict for early
 // development. #![deny(future_incompatible, missing_docs, rust_2018_idioms,
 // unused, warnings)]
+extern crate alloc;
+
 #[doc = include_str!("../README.md")]
 pub mod klv;
+pub mod cursor;
+pub mod diff;
+pub mod export;
+pub mod flags;
+pub mod generator;
+pub mod imap;
 pub mod klv_packet;
+pub mod klv_packet_ref;
 pub mod klv_value;
+pub mod local_set;
+pub mod miis;
+#[cfg(feature = "mpegts")]
+pub mod mpegts;
+pub mod options;
+pub mod rules;
+pub mod sdcc;
+#[cfg(feature = "tokio")]
+pub mod stream;
 pub mod tag;
+pub mod track;
+pub mod units;
+pub mod weapon;
 
 #[derive(Debug, thiserror::Error)]
 pub enum ErrorKind {
@@ -14,4 +36,27 @@ pub enum ErrorKind {
     UnsupportedTag(usize),
     #[error("Checksum for KLV packet is invalid")]
     InvalidChecksum,
+    #[error("Value for tag `{0}` is malformed")]
+    MalformedValue(usize),
+    #[error("Value for tag `{0}` can't be {1} bytes long")]
+    InvalidValueLength(usize, usize),
+    #[error("Cannot parse `{0}`")]
+    InvalidString(alloc::string::String),
+    #[error("Length of a KLV field exceeds the data available")]
+    InvalidLength,
+    #[error("Decoding the value of tag `{0}` is not implemented")]
+    Unimplemented(usize),
+    #[error("Packet of {0} bytes exceeds the maximum packet size")]
+    PacketTooLarge(usize),
+    #[error("Local sets are nested deeper than {0} levels")]
+    TooDeep(usize),
+    #[error("Tag `{0}` appears more than once")]
+    DuplicateTag(usize),
+    #[error("Invalid rule, {0}")]
+    InvalidRule(alloc::string::String),
+    #[error("Invalid scenario, {0}")]
+    InvalidScenario(alloc::string::String),
+    #[cfg(feature = "std")]
+    #[error("Failed to read from the stream")]
+    Io(#[from] std::io::Error),
 }
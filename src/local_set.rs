@@ -0,0 +1,1036 @@
+//! Information from this page was gathered from SMPTE ST 336, "Data Encoding
+//! Protocol Using Key-Length-Value", and MISB ST 0107.
+//!
+//! Every KLV local set starts with a 16 byte universal key followed by the
+//! length of the set and then a series of tag, length, value fields. How the
+//! length of the set, the tags and the lengths of the fields are encoded
+//! differs between local sets, as does how the set is protected by a
+//! checksum. A [`LocalSetDefinition`] describes all of these for one set so
+//! that the same parser can be used for any of them.
+//!
+//! The UAS Datalink Local Set (MISB ST 0601) and the VMTI Local Set (MISB ST
+//! 0903) are defined by this crate. Other sets, such as those from MISB ST
+//! 1107 which this crate doesn't define yet, can be added to a [`Registry`]
+//! by the user.
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+#[cfg(feature = "search")]
+use memmem::Searcher;
+#[cfg(feature = "search")]
+use memmem::TwoWaySearcher;
+#[cfg(feature = "tracing")]
+use tracing::debug;
+#[cfg(feature = "tracing")]
+use tracing::trace;
+
+use crate::ErrorKind;
+use crate::cursor::Cursor;
+use crate::klv_packet::UAS_LENGTH_ENCODING;
+use crate::klv_packet::UAS_LOCAL_SET_UNIVERSAL_LABEL;
+use crate::klv_packet::UAS_TAG_ENCODING;
+use crate::klv_value::KlvValue;
+use crate::klv_value::KlvValueType;
+use crate::options::ParseOptions;
+use crate::tag::Tag;
+
+/// A 16 byte SMPTE universal key.
+pub type UniversalKey = [u8; 16];
+
+pub const VMTI_LOCAL_SET_UNIVERSAL_LABEL: UniversalKey = [
+    0x06, 0x0E, 0x2B, 0x34, 0x02, 0x0B, 0x01, 0x01, 0x0E, 0x01, 0x03, 0x03,
+    0x06, 0x00, 0x00, 0x00,
+];
+
+/// How a tag or length is encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// BER short or long form.
+    Ber,
+    /// BER-OID, 7 bits per byte with a continuation bit.
+    BerOid,
+    /// Big endian unsigned integer of a fixed number of bytes. SMPTE ST 336
+    /// allows 1, 2 or 4 bytes.
+    Fixed(usize),
+}
+
+impl Encoding {
+    /// Check that values in this encoding take at least one byte and fit in
+    /// a `usize`, failing with [`ErrorKind::InvalidLength`] otherwise.
+    ///
+    /// A fixed encoding of zero bytes would never advance through a set.
+    pub fn validate(self) -> Result<(), ErrorKind> {
+        match self {
+            Encoding::Fixed(length)
+                if length == 0 || length > size_of::<usize>() =>
+            {
+                Err(ErrorKind::InvalidLength)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Read a value in this encoding from the buffer.
+    pub fn read(self, buf: &mut Cursor<&[u8]>) -> Result<usize, ErrorKind> {
+        match self {
+            Encoding::Ber => read_ber(buf),
+            Encoding::BerOid => read_ber_oid(buf),
+            Encoding::Fixed(length) => read_uint(buf, length),
+        }
+    }
+
+    /// Append `value` in this encoding to `out`.
+    ///
+    /// BER values use the short form if they fit in it and the shortest long
+    /// form otherwise.
+    pub fn write(
+        self,
+        value: usize,
+        out: &mut Vec<u8>,
+    ) -> Result<(), ErrorKind> {
+        const SIZE: usize = size_of::<usize>();
+        let bytes = value.to_be_bytes();
+        match self {
+            Encoding::Ber if value < 0x80 => out.push(value as u8),
+            Encoding::Ber => {
+                let length = SIZE - value.leading_zeros() as usize / 8;
+                out.push(0x80 | length as u8);
+                out.extend_from_slice(&bytes[SIZE - length..]);
+            }
+            Encoding::BerOid => {
+                let groups =
+                    (usize::BITS - value.leading_zeros()).div_ceil(7).max(1);
+                for group in (0..groups).rev() {
+                    let byte = (value >> (7 * group)) as u8 & 0x7F;
+                    out.push(if group > 0 { byte | 0x80 } else { byte });
+                }
+            }
+            Encoding::Fixed(length) => {
+                if length > SIZE || length < SIZE && value >> (8 * length) != 0
+                {
+                    return Err(ErrorKind::InvalidLength);
+                }
+                out.extend_from_slice(&bytes[SIZE - length..]);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read a BER short or long form value from the buffer.
+pub(crate) fn read_ber(buf: &mut Cursor<&[u8]>) -> Result<usize, ErrorKind> {
+    let first = read_uint(buf, 1)?;
+
+    // If the first bit is a 1 then this is a long-form BER.
+    if first & 0x80 != 0 {
+        return read_uint(buf, first & 0x7F);
+    }
+
+    Ok(first)
+}
+
+/// Read a BER-OID value from the buffer.
+pub(crate) fn read_ber_oid(
+    buf: &mut Cursor<&[u8]>,
+) -> Result<usize, ErrorKind> {
+    let mut value: usize = 0;
+    loop {
+        let byte = read_uint(buf, 1)?;
+        value = value.checked_mul(1 << 7).ok_or(ErrorKind::InvalidLength)?
+            | (byte & 0x7F);
+
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+}
+
+/// Read a big endian unsigned integer of `length` bytes from the buffer.
+fn read_uint(
+    buf: &mut Cursor<&[u8]>,
+    length: usize,
+) -> Result<usize, ErrorKind> {
+    if length > size_of::<usize>() {
+        return Err(ErrorKind::InvalidLength);
+    }
+
+    let mut bytes = [0; size_of::<usize>()];
+    buf.read_exact(&mut bytes[size_of::<usize>() - length..])
+        .map_err(|_| ErrorKind::InvalidLength)?;
+    Ok(usize::from_be_bytes(bytes))
+}
+
+/// How a local set is protected by a checksum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChecksumRule {
+    /// The set has no checksum.
+    None,
+    /// 16-bit running sum stored in `tag`, as used by MISB ST 0601.
+    Bcc16 { tag: usize },
+    /// CRC-16-CCITT stored in `tag`, as used by MISB ST 0903.
+    Crc16Ccitt { tag: usize },
+}
+
+impl ChecksumRule {
+    /// The tag that holds the checksum.
+    pub fn tag(self) -> Option<usize> {
+        match self {
+            ChecksumRule::None => None,
+            ChecksumRule::Bcc16 { tag } | ChecksumRule::Crc16Ccitt { tag } => {
+                Some(tag)
+            }
+        }
+    }
+
+    /// Calculate the checksum of `bytes`.
+    ///
+    /// This should be the entire set, starting with the universal key and
+    /// ending with the length of the checksum field.
+    pub fn calculate(self, bytes: &[u8]) -> Option<u16> {
+        match self {
+            ChecksumRule::None => None,
+            ChecksumRule::Bcc16 { .. } => {
+                Some(bytes.iter().enumerate().fold(0u16, |bcc, (idx, byte)| {
+                    bcc.wrapping_add((*byte as u16) << (8 * ((idx + 1) % 2)))
+                }))
+            }
+            ChecksumRule::Crc16Ccitt { .. } => {
+                Some(bytes.iter().fold(0xFFFFu16, |crc, byte| {
+                    (0..8).fold(crc ^ ((*byte as u16) << 8), |crc, _| {
+                        if crc & 0x8000 != 0 {
+                            (crc << 1) ^ 0x1021
+                        } else {
+                            crc << 1
+                        }
+                    })
+                }))
+            }
+        }
+    }
+}
+
+/// Whether the checksum of a set matches its contents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChecksumStatus {
+    /// The checksum matches, or the set isn't protected by a checksum.
+    Valid,
+    /// The checksum field is missing or isn't the last field of the set.
+    Missing,
+    /// The checksum in the set doesn't match the calculated checksum.
+    Mismatch { found: u16, calculated: u16 },
+}
+
+/// What to do with a set whose checksum is missing or doesn't match.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChecksumPolicy {
+    /// Reject the set with [`ErrorKind::InvalidChecksum`].
+    #[default]
+    Strict,
+    /// Accept the set and report its [`ChecksumStatus`] to the caller.
+    Lenient,
+}
+
+impl ChecksumPolicy {
+    /// Apply the policy to the status of a set's checksum.
+    pub fn check(
+        self,
+        status: ChecksumStatus,
+    ) -> Result<ChecksumStatus, ErrorKind> {
+        match (self, status) {
+            (ChecksumPolicy::Strict, ChecksumStatus::Valid)
+            | (ChecksumPolicy::Lenient, _) => Ok(status),
+            (ChecksumPolicy::Strict, _) => Err(ErrorKind::InvalidChecksum),
+        }
+    }
+}
+
+/// Recalculate the checksum of the first set with `key` in `bytes` and write
+/// it into the set's checksum field, returning the new checksum.
+///
+/// The checksum field must already be the last field of the set. If `search`
+/// is set the key may be anywhere in `bytes`. Returns `Ok(None)` if there is
+/// no set with `key` in `bytes`.
+pub(crate) fn repair_checksum(
+    bytes: &mut [u8],
+    key: &UniversalKey,
+    set_length_encoding: Encoding,
+    tag_encoding: Encoding,
+    length_encoding: Encoding,
+    rule: ChecksumRule,
+    search: bool,
+) -> Result<Option<u16>, ErrorKind> {
+    let Some(frame) = Frame::parse(
+        bytes,
+        key,
+        set_length_encoding,
+        tag_encoding,
+        length_encoding,
+        search,
+        usize::MAX,
+    )?
+    else {
+        return Ok(None);
+    };
+    if frame.checksum_field(rule).is_none() {
+        return Err(ErrorKind::InvalidChecksum);
+    }
+    let (start, end) = (frame.start, frame.end);
+
+    let checksum = rule
+        .calculate(&bytes[start..end - 2])
+        .ok_or(ErrorKind::InvalidChecksum)?;
+    bytes[end - 2..end].copy_from_slice(&checksum.to_be_bytes());
+    Ok(Some(checksum))
+}
+
+/// The raw layout of a local set found in a byte buffer.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Frame<'a> {
+    /// Index of the first byte of the universal key.
+    pub start: usize,
+    /// Index one past the last byte of the set.
+    pub end: usize,
+    /// The fields of the set, after the key and length.
+    body: &'a [u8],
+    tag_encoding: Encoding,
+    length_encoding: Encoding,
+}
+
+/// Iterator over the tags and values of the fields in a [`Frame`], in order.
+#[derive(Clone, Debug)]
+pub(crate) struct Fields<'a> {
+    body: &'a [u8],
+    tag_encoding: Encoding,
+    length_encoding: Encoding,
+}
+
+impl<'a> Fields<'a> {
+    /// Read the next field and advance past it.
+    fn try_next(&mut self) -> Result<(usize, &'a [u8]), ErrorKind> {
+        let mut buffer = Cursor::new(self.body);
+        let tag = self.tag_encoding.read(&mut buffer)?;
+        let length = self.length_encoding.read(&mut buffer)?;
+        let value_start = buffer.position() as usize;
+        let value_end = value_start
+            .checked_add(length)
+            .filter(|value_end| *value_end <= self.body.len())
+            .ok_or(ErrorKind::InvalidLength)?;
+
+        let value = &self.body[value_start..value_end];
+        self.body = &self.body[value_end..];
+        Ok((tag, value))
+    }
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = (usize, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.body.is_empty() {
+            return None;
+        }
+        // Frames are validated when parsed so this can only fail if the
+        // fields were not.
+        self.try_next().ok()
+    }
+}
+
+impl<'a> Frame<'a> {
+    /// Find the local set starting with `key` in `bytes` and split it into
+    /// its fields.
+    ///
+    /// If `search` is set the key may be anywhere in `bytes`. Otherwise it
+    /// must be at the start. `Ok(None)` is returned if the key cannot be
+    /// found. Sets longer than `max_size`, counted from the start of the key,
+    /// are rejected before their fields are read.
+    pub fn parse(
+        bytes: &'a [u8],
+        key: &UniversalKey,
+        set_length_encoding: Encoding,
+        tag_encoding: Encoding,
+        length_encoding: Encoding,
+        search: bool,
+        max_size: usize,
+    ) -> Result<Option<Frame<'a>>, ErrorKind> {
+        set_length_encoding.validate()?;
+        tag_encoding.validate()?;
+        length_encoding.validate()?;
+        let Some(start) = find_key(bytes, key, search) else {
+            return Ok(None);
+        };
+
+        #[cfg(feature = "tracing")]
+        {
+            trace!("Parsing KLV local set: {:02X?}", bytes);
+            trace!("Start index [{}]", start);
+        }
+
+        // Create a cursor for the bytes so we can keep track of what has been
+        // read without a bunch of magic numbers.
+        let mut buffer = Cursor::new(bytes);
+        buffer.set_position((start + key.len()) as u64);
+
+        let length = set_length_encoding.read(&mut buffer)?;
+        #[cfg(feature = "tracing")]
+        trace!("Length of set [{}]", length);
+
+        // Index in the data where the set ends
+        let end = (buffer.position() as usize)
+            .checked_add(length)
+            .ok_or(ErrorKind::InvalidLength)?;
+        if end - start > max_size {
+            return Err(ErrorKind::PacketTooLarge(end - start));
+        }
+        if end > bytes.len() {
+            return Err(ErrorKind::InvalidLength);
+        }
+
+        #[cfg(feature = "tracing")]
+        trace!("Set end [{}]", end);
+
+        let frame = Frame {
+            start,
+            end,
+            body: &bytes[buffer.position() as usize..end],
+            tag_encoding,
+            length_encoding,
+        };
+        frame.walk().map(Some)
+    }
+
+    /// Split the value of a local set nested inside another set into its
+    /// fields. Nested sets have no key or length of their own.
+    pub fn nested(
+        body: &'a [u8],
+        tag_encoding: Encoding,
+        length_encoding: Encoding,
+    ) -> Result<Frame<'a>, ErrorKind> {
+        Frame { start: 0, end: body.len(), body, tag_encoding, length_encoding }
+            .walk()
+    }
+
+    /// Walk the fields once so that later iterations can't fail.
+    fn walk(self) -> Result<Frame<'a>, ErrorKind> {
+        let mut fields = self.fields();
+        while !fields.body.is_empty() {
+            let (_tag, _value) = fields.try_next()?;
+            #[cfg(feature = "tracing")]
+            trace!("Found tag [{}] with length [{}]", _tag, _value.len());
+        }
+        Ok(self)
+    }
+
+    /// Tags and values of every field in the set, in order.
+    pub fn fields(&self) -> Fields<'a> {
+        Fields {
+            body: self.body,
+            tag_encoding: self.tag_encoding,
+            length_encoding: self.length_encoding,
+        }
+    }
+
+    /// The value of the checksum field for `rule`, if it is the last field
+    /// in the set and two bytes long.
+    fn checksum_field(&self, rule: ChecksumRule) -> Option<u16> {
+        let tag = rule.tag()?;
+        let (last, checksum) = self.fields().last()?;
+        if last != tag {
+            return None;
+        }
+        Some(u16::from_be_bytes(checksum.try_into().ok()?))
+    }
+
+    /// Compare the checksum of this set against `rule`.
+    pub fn checksum_status(
+        &self,
+        bytes: &[u8],
+        rule: ChecksumRule,
+    ) -> ChecksumStatus {
+        if rule == ChecksumRule::None {
+            return ChecksumStatus::Valid;
+        }
+        let Some(packet_checksum) = self.checksum_field(rule) else {
+            return ChecksumStatus::Missing;
+        };
+
+        // The checksum covers everything up to, but not including, the
+        // checksum value itself.
+        let calculated_checksum = rule
+            .calculate(&bytes[self.start..self.end - 2])
+            .unwrap_or(packet_checksum);
+
+        if packet_checksum != calculated_checksum {
+            #[cfg(feature = "tracing")]
+            debug!(
+                "Checksum for set [{}] vs calculated checksum [{}]",
+                packet_checksum, calculated_checksum
+            );
+            return ChecksumStatus::Mismatch {
+                found: packet_checksum,
+                calculated: calculated_checksum,
+            };
+        }
+
+        ChecksumStatus::Valid
+    }
+}
+
+/// Find the first occurrence of `key` in `bytes`, or check that `bytes`
+/// starts with `key` if `search` isn't set.
+fn find_key(bytes: &[u8], key: &UniversalKey, search: bool) -> Option<usize> {
+    if !search {
+        // Check if the first bytes are exactly the key.
+        return bytes.starts_with(key).then_some(0);
+    }
+    #[cfg(feature = "search")]
+    {
+        TwoWaySearcher::new(key).search_in(bytes)
+    }
+    #[cfg(not(feature = "search"))]
+    {
+        bytes.windows(key.len()).position(|window| window == key)
+    }
+}
+
+/// Parse a single SMPTE ST 336 universal key and BER length triplet from the
+/// start of `bytes`, whatever its key.
+///
+/// Returns the key, the value and the total number of bytes used.
+pub fn parse_triplet(
+    bytes: &[u8],
+) -> Result<(UniversalKey, &[u8], usize), ErrorKind> {
+    let key: UniversalKey = bytes
+        .get(..16)
+        .and_then(|key| key.try_into().ok())
+        .ok_or(ErrorKind::InvalidLength)?;
+
+    let mut buffer = Cursor::new(bytes);
+    buffer.set_position(key.len() as u64);
+    let length = read_ber(&mut buffer)?;
+    let start = buffer.position() as usize;
+    let value = start
+        .checked_add(length)
+        .and_then(|end| bytes.get(start..end))
+        .ok_or(ErrorKind::InvalidLength)?;
+
+    Ok((key, value, start + length))
+}
+
+/// A single entry in the dictionary of a [`LocalSetDefinition`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TagDefinition {
+    pub name: Arc<str>,
+    pub value_type: KlvValueType,
+}
+
+/// Describes how to find, split, verify and decode one kind of local set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocalSetDefinition {
+    pub name: Arc<str>,
+    pub key: UniversalKey,
+    /// Encoding of the length of the whole set, after the key.
+    pub set_length_encoding: Encoding,
+    pub tag_encoding: Encoding,
+    pub length_encoding: Encoding,
+    pub checksum: ChecksumRule,
+    pub tags: BTreeMap<usize, TagDefinition>,
+}
+
+impl LocalSetDefinition {
+    /// Create a definition with an empty dictionary.
+    ///
+    /// Fails with [`ErrorKind::InvalidLength`] if any of the encodings is a
+    /// fixed encoding of zero bytes or of more bytes than a `usize`.
+    pub fn new(
+        name: &str,
+        key: UniversalKey,
+        set_length_encoding: Encoding,
+        tag_encoding: Encoding,
+        length_encoding: Encoding,
+        checksum: ChecksumRule,
+    ) -> Result<LocalSetDefinition, ErrorKind> {
+        set_length_encoding.validate()?;
+        tag_encoding.validate()?;
+        length_encoding.validate()?;
+        Ok(LocalSetDefinition::with_layout(
+            name,
+            key,
+            set_length_encoding,
+            tag_encoding,
+            length_encoding,
+            checksum,
+        ))
+    }
+
+    /// Create a definition whose encodings are already known to be valid.
+    fn with_layout(
+        name: &str,
+        key: UniversalKey,
+        set_length_encoding: Encoding,
+        tag_encoding: Encoding,
+        length_encoding: Encoding,
+        checksum: ChecksumRule,
+    ) -> LocalSetDefinition {
+        LocalSetDefinition {
+            name: name.into(),
+            key,
+            set_length_encoding,
+            tag_encoding,
+            length_encoding,
+            checksum,
+            tags: BTreeMap::new(),
+        }
+    }
+
+    /// Add a tag to the dictionary of this definition.
+    pub fn with_tag(
+        mut self,
+        id: usize,
+        name: &str,
+        value_type: KlvValueType,
+    ) -> LocalSetDefinition {
+        self.tags.insert(id, TagDefinition { name: name.into(), value_type });
+        self
+    }
+
+    /// The UAS Datalink Local Set from MISB ST 0601.
+    pub fn uas() -> LocalSetDefinition {
+        Tag::VARIANTS.iter().fold(
+            LocalSetDefinition::with_layout(
+                "UAS Datalink Local Set",
+                UAS_LOCAL_SET_UNIVERSAL_LABEL,
+                UAS_LENGTH_ENCODING,
+                UAS_TAG_ENCODING,
+                UAS_LENGTH_ENCODING,
+                ChecksumRule::Bcc16 { tag: Tag::Checksum.id() },
+            ),
+            |definition, tag| {
+                definition.with_tag(tag.id(), tag.string(), tag.tag_type())
+            },
+        )
+    }
+
+    /// The Video Moving Target Indicator Local Set from MISB ST 0903.
+    pub fn vmti() -> LocalSetDefinition {
+        LocalSetDefinition::with_layout(
+            "VMTI Local Set",
+            VMTI_LOCAL_SET_UNIVERSAL_LABEL,
+            Encoding::Ber,
+            Encoding::BerOid,
+            Encoding::Ber,
+            ChecksumRule::Crc16Ccitt { tag: 1 },
+        )
+        .with_tag(1, "Checksum", KlvValueType::Uint16)
+        .with_tag(2, "PrecisionTimeStamp", KlvValueType::Uint64)
+        .with_tag(3, "SystemName", KlvValueType::Utf8)
+        .with_tag(4, "Version", KlvValueType::Uint)
+        .with_tag(5, "TotalTargetsDetected", KlvValueType::Uint)
+        .with_tag(6, "TargetsReported", KlvValueType::Uint)
+        .with_tag(7, "FrameNumber", KlvValueType::Uint)
+        .with_tag(8, "FrameWidth", KlvValueType::Uint)
+        .with_tag(9, "FrameHeight", KlvValueType::Uint)
+        .with_tag(10, "SourceSensor", KlvValueType::Utf8)
+        .with_tag(11, "HorizontalFieldOfView", KlvValueType::IMAPB)
+        .with_tag(12, "VerticalFieldOfView", KlvValueType::IMAPB)
+        .with_tag(13, "MiisId", KlvValueType::Byte)
+        .with_tag(101, "VTargetSeries", KlvValueType::VLP)
+        .with_tag(102, "AlgorithmSeries", KlvValueType::VLP)
+        .with_tag(103, "OntologySeries", KlvValueType::VLP)
+    }
+
+    /// Parse the local set described by this definition from `bytes`.
+    pub fn parse(&self, bytes: &[u8]) -> Result<Option<LocalSet>, ErrorKind> {
+        self.parse_with(bytes, &ParseOptions::default())
+    }
+
+    /// Parse the local set described by this definition from `bytes`, using
+    /// [`ParseOptions::search`], [`ParseOptions::checksum`] and
+    /// [`ParseOptions::max_packet_size`] from `options`.
+    pub fn parse_with(
+        &self,
+        bytes: &[u8],
+        options: &ParseOptions,
+    ) -> Result<Option<LocalSet>, ErrorKind> {
+        let Some(frame) = Frame::parse(
+            bytes,
+            &self.key,
+            self.set_length_encoding,
+            self.tag_encoding,
+            self.length_encoding,
+            options.search,
+            options.max_packet_size,
+        )?
+        else {
+            return Ok(None);
+        };
+
+        options.checksum.check(frame.checksum_status(bytes, self.checksum))?;
+
+        let fields = frame
+            .fields()
+            .map(|(tag, value)| LocalSetField {
+                tag,
+                definition: self.tags.get(&tag).cloned(),
+                raw: value.into(),
+            })
+            .collect();
+
+        Ok(Some(LocalSet { name: self.name.clone(), key: self.key, fields }))
+    }
+}
+
+/// One field of a generic [`LocalSet`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocalSetField {
+    tag: usize,
+    definition: Option<TagDefinition>,
+    raw: Box<[u8]>,
+}
+
+impl LocalSetField {
+    pub fn tag(&self) -> usize {
+        self.tag
+    }
+
+    /// The name of the tag from the dictionary, if it is known.
+    pub fn name(&self) -> Option<&str> {
+        Some(&self.definition.as_ref()?.name)
+    }
+
+    /// The raw bytes of the value.
+    pub fn raw(&self) -> &[u8] {
+        &self.raw
+    }
+
+    /// Decode the value using the type from the dictionary.
+    ///
+    /// Returns `None` if the tag is not in the dictionary.
+    pub fn value(&self) -> Option<Result<KlvValue, ErrorKind>> {
+        let definition = self.definition.as_ref()?;
+        Some(KlvValue::from_type(definition.value_type, self.tag, &self.raw))
+    }
+}
+
+/// A local set parsed using a [`LocalSetDefinition`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocalSet {
+    name: Arc<str>,
+    key: UniversalKey,
+    fields: Vec<LocalSetField>,
+}
+
+impl LocalSet {
+    /// The name of the definition used to parse this set.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn key(&self) -> &UniversalKey {
+        &self.key
+    }
+
+    /// All fields of the set, in the order they were received.
+    pub fn fields(&self) -> &[LocalSetField] {
+        &self.fields
+    }
+
+    /// The first field with the given tag.
+    pub fn get(&self, tag: usize) -> Option<&LocalSetField> {
+        self.fields.iter().find(|field| field.tag == tag)
+    }
+}
+
+/// A collection of local set definitions used to detect and parse sets.
+#[derive(Clone, Debug, Default)]
+pub struct Registry {
+    definitions: Vec<LocalSetDefinition>,
+}
+
+impl Registry {
+    /// Create a registry with no definitions.
+    pub fn new() -> Registry {
+        Registry::default()
+    }
+
+    /// Create a registry containing the definitions supplied by this crate.
+    pub fn with_defaults() -> Registry {
+        let mut registry = Registry::new();
+        registry.register(LocalSetDefinition::uas());
+        registry.register(LocalSetDefinition::vmti());
+        registry
+    }
+
+    /// Add a definition to the registry, replacing any with the same key.
+    pub fn register(&mut self, definition: LocalSetDefinition) {
+        self.definitions.retain(|d| d.key != definition.key);
+        self.definitions.push(definition);
+    }
+
+    /// Get the definition for the given key.
+    pub fn definition(
+        &self,
+        key: &UniversalKey,
+    ) -> Option<&LocalSetDefinition> {
+        self.definitions.iter().find(|d| &d.key == key)
+    }
+
+    /// Find the first registered local set in `bytes` and return its
+    /// definition and the index its key starts at.
+    pub fn detect(&self, bytes: &[u8]) -> Option<(usize, &LocalSetDefinition)> {
+        self.detect_with(bytes, &ParseOptions::default())
+    }
+
+    /// Find the first registered local set in `bytes`, only looking past the
+    /// start of `bytes` if [`ParseOptions::search`] is set.
+    pub fn detect_with(
+        &self,
+        bytes: &[u8],
+        options: &ParseOptions,
+    ) -> Option<(usize, &LocalSetDefinition)> {
+        self.definitions
+            .iter()
+            .filter_map(|d| Some((find_key(bytes, &d.key, options.search)?, d)))
+            .min_by_key(|(idx, _)| *idx)
+    }
+
+    /// Parse the first registered local set found in `bytes`.
+    pub fn parse(&self, bytes: &[u8]) -> Result<Option<LocalSet>, ErrorKind> {
+        self.parse_with(bytes, &ParseOptions::default())
+    }
+
+    /// Parse the first registered local set found in `bytes` using
+    /// `options`.
+    pub fn parse_with(
+        &self,
+        bytes: &[u8],
+        options: &ParseOptions,
+    ) -> Result<Option<LocalSet>, ErrorKind> {
+        let Some((start, definition)) = self.detect_with(bytes, options) else {
+            return Ok(None);
+        };
+        definition.parse_with(&bytes[start..], options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use test_case::test_case;
+
+    use super::ChecksumPolicy;
+    use super::ChecksumRule;
+    use super::Encoding;
+    use super::LocalSetDefinition;
+    use super::Registry;
+    use super::VMTI_LOCAL_SET_UNIVERSAL_LABEL;
+    use super::parse_triplet;
+    use crate::ErrorKind;
+    use crate::cursor::Cursor;
+    use crate::klv_packet::UAS_CHECKSUM;
+    use crate::klv_packet::UAS_LOCAL_SET_UNIVERSAL_LABEL;
+    use crate::klv_value::KlvValue;
+    use crate::klv_value::KlvValueType;
+    use crate::options::ParseOptions;
+    use crate::tag::Tag;
+
+    fn vmti_set() -> Vec<u8> {
+        let mut bytes = Vec::from(VMTI_LOCAL_SET_UNIVERSAL_LABEL);
+        // System name, then the checksum tag and length.
+        bytes.extend_from_slice(&[
+            0x09, 0x03, 0x03, b'E', b'O', b'1', 0x01, 0x02,
+        ]);
+        let crc =
+            ChecksumRule::Crc16Ccitt { tag: 1 }.calculate(&bytes).unwrap();
+        bytes.extend_from_slice(&crc.to_be_bytes());
+        bytes
+    }
+
+    #[test_case(Encoding::Ber, &[0x81, 0xF1], 241; "BER")]
+    #[test_case(Encoding::BerOid, &[0x81, 0x00], 128; "BER-OID")]
+    #[test_case(Encoding::Fixed(2), &[0x01, 0x02], 258; "Two bytes")]
+    #[test_case(Encoding::Fixed(4), &[0x00, 0x00, 0x01, 0x00], 256; "Four bytes")]
+    #[test_case(Encoding::Ber, &[0x7F], 0x7F; "BER short form")]
+    #[test_case(Encoding::Ber, &[0x81, 0x80], 0x80; "BER one byte long form")]
+    #[test_case(Encoding::Ber, &[0x81, 0xFF], 0xFF; "BER largest one byte")]
+    #[test_case(Encoding::Ber, &[0x82, 0x01, 0x00], 0x100; "BER two bytes")]
+    #[test_case(Encoding::Ber, &[0x88, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF], usize::MAX; "BER eight bytes")]
+    #[test_case(Encoding::BerOid, &[0x7F], 127; "BER-OID one byte")]
+    #[test_case(Encoding::BerOid, &[0x81, 0x0F], 143; "BER-OID tag 143")]
+    #[test_case(Encoding::BerOid, &[0xFF, 0x7F], 16383; "BER-OID largest two bytes")]
+    #[test_case(Encoding::BerOid, &[0x81, 0x80, 0x00], 16384; "BER-OID three bytes")]
+    fn encoding(encoding: Encoding, bytes: &[u8], value: usize) {
+        let mut buf = Cursor::new(bytes);
+        assert_eq!(encoding.read(&mut buf).unwrap(), value);
+
+        let mut out = Vec::new();
+        encoding.write(value, &mut out).unwrap();
+        assert_eq!(out, bytes);
+    }
+
+    #[test]
+    fn write_too_large() {
+        assert!(Encoding::Fixed(1).write(256, &mut Vec::new()).is_err());
+        assert!(Encoding::Fixed(9).write(0, &mut Vec::new()).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn ber_round_trip(value in any::<usize>()) {
+            round_trip(Encoding::Ber, value)?;
+        }
+
+        #[test]
+        fn ber_oid_round_trip(value in any::<usize>()) {
+            round_trip(Encoding::BerOid, value)?;
+        }
+
+        #[test]
+        fn fixed_round_trip(size in 1usize..=8, value in any::<u64>()) {
+            let value = (value >> (64 - 8 * size)) as usize;
+            round_trip(Encoding::Fixed(size), value)?;
+        }
+    }
+
+    fn round_trip(
+        encoding: Encoding,
+        value: usize,
+    ) -> Result<(), TestCaseError> {
+        let mut out = Vec::new();
+        encoding.write(value, &mut out).unwrap();
+        let mut buf = Cursor::new(&out[..]);
+        prop_assert_eq!(encoding.read(&mut buf).unwrap(), value);
+        prop_assert_eq!(buf.position(), out.len() as u64);
+        Ok(())
+    }
+
+    #[test]
+    fn crc16_ccitt() {
+        let rule = ChecksumRule::Crc16Ccitt { tag: 1 };
+        assert_eq!(rule.calculate(b"123456789"), Some(0x29B1));
+    }
+
+    #[test]
+    fn vmti() {
+        let set =
+            Registry::with_defaults().parse(&vmti_set()).unwrap().unwrap();
+        assert_eq!(set.name(), "VMTI Local Set");
+        let name = set.get(3).unwrap();
+        assert_eq!(name.name(), Some("SystemName"));
+        assert!(
+            matches!(name.value(), Some(Ok(KlvValue::Utf8(name))) if &*name == "EO1")
+        );
+    }
+
+    #[test]
+    fn uas_extended_tags() {
+        // Tags 128 to 143 each with a one byte value.
+        let mut body = Vec::new();
+        for tag in 128..=143 {
+            Encoding::BerOid.write(tag, &mut body).unwrap();
+            body.extend_from_slice(&[0x01, 0x01]);
+        }
+        body.extend_from_slice(&[0x01, 0x02]);
+        let mut bytes = Vec::from(UAS_LOCAL_SET_UNIVERSAL_LABEL);
+        Encoding::Ber.write(body.len() + 2, &mut bytes).unwrap();
+        bytes.extend_from_slice(&body);
+        let checksum = UAS_CHECKSUM.calculate(&bytes).unwrap();
+        bytes.extend_from_slice(&checksum.to_be_bytes());
+
+        let set = Registry::with_defaults().parse(&bytes).unwrap().unwrap();
+        assert_eq!(set.name(), "UAS Datalink Local Set");
+        for tag in 128..=143 {
+            let field = set.get(tag).unwrap();
+            assert_eq!(field.name(), Some(Tag::from(tag).string()));
+        }
+    }
+
+    #[test]
+    fn search() {
+        let mut bytes = vec![0x00, 0xFF];
+        bytes.extend(vmti_set());
+        let registry = Registry::with_defaults();
+
+        let options = ParseOptions { search: false, ..ParseOptions::default() };
+        assert!(registry.detect_with(&bytes, &options).is_none());
+        assert!(registry.parse_with(&bytes, &options).unwrap().is_none());
+
+        let options = ParseOptions { search: true, ..options };
+        let (start, definition) =
+            registry.detect_with(&bytes, &options).unwrap();
+        assert_eq!((start, &*definition.name), (2, "VMTI Local Set"));
+        let set = registry.parse_with(&bytes, &options).unwrap().unwrap();
+        assert_eq!(set.name(), "VMTI Local Set");
+    }
+
+    #[test]
+    fn lenient_checksum() {
+        let mut bytes = vmti_set();
+        *bytes.last_mut().unwrap() ^= 0xFF;
+        let options = ParseOptions {
+            checksum: ChecksumPolicy::Lenient,
+            ..ParseOptions::default()
+        };
+        let set = LocalSetDefinition::vmti().parse_with(&bytes, &options);
+        assert!(set.unwrap().is_some());
+    }
+
+    #[test]
+    fn bad_checksum() {
+        let mut bytes = vmti_set();
+        *bytes.last_mut().unwrap() ^= 0xFF;
+        assert!(LocalSetDefinition::vmti().parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn user_definition() {
+        let key = [0xAA; 16];
+        let mut registry = Registry::new();
+        registry.register(
+            LocalSetDefinition::new(
+                "Custom",
+                key,
+                Encoding::Fixed(2),
+                Encoding::Fixed(2),
+                Encoding::Fixed(1),
+                ChecksumRule::None,
+            )
+            .unwrap()
+            .with_tag(0x0102, "Count", KlvValueType::Uint8),
+        );
+
+        let mut bytes = Vec::from(key);
+        bytes.extend_from_slice(&[0x00, 0x04, 0x01, 0x02, 0x01, 0x2A]);
+        let set = registry.parse(&bytes).unwrap().unwrap();
+        assert!(matches!(
+            set.get(0x0102).unwrap().value(),
+            Some(Ok(KlvValue::Uint8(42)))
+        ));
+    }
+
+    #[test_case(Encoding::Fixed(1), Encoding::Fixed(0); "Zero byte tags")]
+    #[test_case(Encoding::Fixed(0), Encoding::Ber; "Zero byte set length")]
+    #[test_case(Encoding::Fixed(9), Encoding::Ber; "Wider than usize")]
+    fn invalid_definition(set_length: Encoding, tag: Encoding) {
+        let definition = LocalSetDefinition::new(
+            "Custom",
+            [0xAA; 16],
+            set_length,
+            tag,
+            Encoding::Ber,
+            ChecksumRule::None,
+        );
+        assert!(matches!(definition, Err(ErrorKind::InvalidLength)));
+    }
+
+    #[test]
+    fn triplet() {
+        let mut bytes = vec![0x55; 16];
+        bytes.extend_from_slice(&[0x02, 0x01, 0x02, 0xFF]);
+        let (key, value, length) = parse_triplet(&bytes).unwrap();
+        assert_eq!(key, [0x55; 16]);
+        assert_eq!(value, &[0x01, 0x02]);
+        assert_eq!(length, 19);
+    }
+}
@@ -0,0 +1,315 @@
+//! Information from this page was gathered from MISB ST 1204.3, "Motion
+//! Imagery Identification System (MIIS) - Core Identifier".
+//!
+//! The binary form of the Core Identifier is a version byte, a usage byte and
+//! then up to four 16 byte UUIDs, in order: Sensor, Platform, Window and
+//! Minor. The usage byte describes which of the UUIDs are present:
+//!
+//! | Bits | Meaning                          |
+//! |------|----------------------------------|
+//! | 7-6  | Sensor ID type                   |
+//! | 5-4  | Platform ID type                 |
+//! | 3    | Window ID is present             |
+//! | 2    | Minor ID is present              |
+//! | 1-0  | Reserved                         |
+//!
+//! The text form is the version and usage bytes as hex, a `:`, and then each
+//! present UUID separated by `/`. Each UUID is written as eight groups of four
+//! hex digits separated by `-`. For example:
+//!
+//! `0170:F592-F023-7336-4AF8-AA91-62C0-0F2E-B2DA/16B7-4341-0008-41A0-BE36-5B5A-B4A0-FC6B`
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use core::str::FromStr;
+
+use crate::ErrorKind;
+use crate::tag::Tag;
+
+/// A 16 byte universally unique identifier.
+pub type Uuid = [u8; 16];
+
+/// How a Sensor or Platform ID was assigned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdType {
+    /// No ID is present.
+    None,
+    /// The ID was assigned from a managed list.
+    Managed,
+    /// The ID was generated by software rather than tied to hardware.
+    Virtual,
+    /// The ID is tied to physical hardware.
+    Physical,
+}
+
+impl IdType {
+    fn from_bits(bits: u8) -> IdType {
+        match bits & 0x03 {
+            0 => IdType::None,
+            1 => IdType::Managed,
+            2 => IdType::Virtual,
+            _ => IdType::Physical,
+        }
+    }
+
+    fn bits(self) -> u8 {
+        match self {
+            IdType::None => 0,
+            IdType::Managed => 1,
+            IdType::Virtual => 2,
+            IdType::Physical => 3,
+        }
+    }
+}
+
+/// MIIS Core Identifier used to uniquely identify a Motion Imagery collection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MiisCoreId {
+    version: u8,
+    sensor_id_type: IdType,
+    platform_id_type: IdType,
+    sensor_id: Option<Uuid>,
+    platform_id: Option<Uuid>,
+    window_id: Option<Uuid>,
+    minor_id: Option<Uuid>,
+}
+
+impl MiisCoreId {
+    const WINDOW_BIT: u8 = 0x08;
+    const MINOR_BIT: u8 = 0x04;
+
+    /// Create a new Core Identifier with the given UUIDs.
+    pub fn new(
+        sensor: Option<(IdType, Uuid)>,
+        platform: Option<(IdType, Uuid)>,
+        window_id: Option<Uuid>,
+        minor_id: Option<Uuid>,
+    ) -> MiisCoreId {
+        let (sensor_id_type, sensor_id) = Self::split(sensor);
+        let (platform_id_type, platform_id) = Self::split(platform);
+
+        MiisCoreId {
+            version: 1,
+            sensor_id_type,
+            platform_id_type,
+            sensor_id,
+            platform_id,
+            window_id,
+            minor_id,
+        }
+    }
+
+    fn split(id: Option<(IdType, Uuid)>) -> (IdType, Option<Uuid>) {
+        match id {
+            Some((IdType::None, _)) | None => (IdType::None, None),
+            Some((id_type, uuid)) => (id_type, Some(uuid)),
+        }
+    }
+
+    fn malformed() -> ErrorKind {
+        ErrorKind::MalformedValue(Tag::MiisCoreIdentifier.id())
+    }
+
+    /// Parse the binary form of a Core Identifier.
+    pub fn from_bytes(bytes: &[u8]) -> Result<MiisCoreId, ErrorKind> {
+        let [version, usage, uuids @ ..] = bytes else {
+            return Err(Self::malformed());
+        };
+
+        let mut uuids = uuids.chunks(16);
+        let mut next_uuid = |present: bool| -> Result<Option<Uuid>, ErrorKind> {
+            if !present {
+                return Ok(None);
+            }
+            let uuid = uuids.next().ok_or(Self::malformed())?;
+            Ok(Some(uuid.try_into().map_err(|_| Self::malformed())?))
+        };
+
+        let sensor_id_type = IdType::from_bits(usage >> 6);
+        let platform_id_type = IdType::from_bits(usage >> 4);
+        let sensor_id = next_uuid(sensor_id_type != IdType::None)?;
+        let platform_id = next_uuid(platform_id_type != IdType::None)?;
+        let window_id = next_uuid(usage & Self::WINDOW_BIT != 0)?;
+        let minor_id = next_uuid(usage & Self::MINOR_BIT != 0)?;
+
+        if uuids.next().is_some() {
+            return Err(Self::malformed());
+        }
+
+        Ok(MiisCoreId {
+            version: *version,
+            sensor_id_type,
+            platform_id_type,
+            sensor_id,
+            platform_id,
+            window_id,
+            minor_id,
+        })
+    }
+
+    /// Return the binary form of this Core Identifier.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.version, self.usage()];
+        self.uuids().for_each(|uuid| bytes.extend_from_slice(uuid));
+        bytes
+    }
+
+    /// All UUIDs that are present, in order.
+    fn uuids(&self) -> impl Iterator<Item = &Uuid> {
+        [&self.sensor_id, &self.platform_id, &self.window_id, &self.minor_id]
+            .into_iter()
+            .flatten()
+    }
+
+    /// Version of the Core Identifier.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// The usage byte describing which IDs are present.
+    pub fn usage(&self) -> u8 {
+        let mut usage =
+            self.sensor_id_type.bits() << 6 | self.platform_id_type.bits() << 4;
+        if self.window_id.is_some() {
+            usage |= Self::WINDOW_BIT;
+        }
+        if self.minor_id.is_some() {
+            usage |= Self::MINOR_BIT;
+        }
+        usage
+    }
+
+    /// How the Sensor ID was assigned.
+    pub fn sensor_id_type(&self) -> IdType {
+        self.sensor_id_type
+    }
+
+    /// How the Platform ID was assigned.
+    pub fn platform_id_type(&self) -> IdType {
+        self.platform_id_type
+    }
+
+    /// The Sensor UUID.
+    pub fn sensor_id(&self) -> Option<&Uuid> {
+        self.sensor_id.as_ref()
+    }
+
+    /// The Platform UUID.
+    pub fn platform_id(&self) -> Option<&Uuid> {
+        self.platform_id.as_ref()
+    }
+
+    /// The Window UUID.
+    pub fn window_id(&self) -> Option<&Uuid> {
+        self.window_id.as_ref()
+    }
+
+    /// The Minor UUID.
+    pub fn minor_id(&self) -> Option<&Uuid> {
+        self.minor_id.as_ref()
+    }
+
+    fn parse_uuid(text: &str) -> Option<Uuid> {
+        let hex: String = text.split('-').collect();
+        if hex.len() != 32 {
+            return None;
+        }
+
+        let mut uuid = [0; 16];
+        for (idx, byte) in uuid.iter_mut().enumerate() {
+            *byte =
+                u8::from_str_radix(hex.get(idx * 2..idx * 2 + 2)?, 16).ok()?;
+        }
+        Some(uuid)
+    }
+}
+
+impl Display for MiisCoreId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:02X}{:02X}:", self.version, self.usage())?;
+        for (idx, uuid) in self.uuids().enumerate() {
+            if idx > 0 {
+                write!(f, "/")?;
+            }
+            for (group, pair) in uuid.chunks(2).enumerate() {
+                if group > 0 {
+                    write!(f, "-")?;
+                }
+                write!(f, "{:02X}{:02X}", pair[0], pair[1])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MiisCoreId {
+    type Err = ErrorKind;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || ErrorKind::InvalidString(text.to_string());
+
+        let (header, ids) = text.split_once(':').ok_or_else(invalid)?;
+        if header.len() != 4 {
+            return Err(invalid());
+        }
+        let version = header
+            .get(0..2)
+            .and_then(|v| u8::from_str_radix(v, 16).ok())
+            .ok_or_else(invalid)?;
+        let usage = header
+            .get(2..4)
+            .and_then(|u| u8::from_str_radix(u, 16).ok())
+            .ok_or_else(invalid)?;
+
+        let mut bytes = vec![version, usage];
+        if !ids.is_empty() {
+            for id in ids.split('/') {
+                bytes.extend(Self::parse_uuid(id).ok_or_else(invalid)?);
+            }
+        }
+
+        Self::from_bytes(&bytes).map_err(|_| invalid())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IdType;
+    use super::MiisCoreId;
+
+    const TEXT: &str = "0170:F592-F023-7336-4AF8-AA91-62C0-0F2E-B2DA/16B7-4341-0008-41A0-BE36-5B5A-B4A0-FC6B";
+
+    #[test]
+    fn text_round_trip() {
+        let id: MiisCoreId = TEXT.parse().unwrap();
+        assert_eq!(id.version(), 1);
+        assert_eq!(id.sensor_id_type(), IdType::Managed);
+        assert_eq!(id.platform_id_type(), IdType::Physical);
+        assert_eq!(id.sensor_id().unwrap()[..2], [0xF5, 0x92]);
+        assert_eq!(id.window_id(), None);
+        assert_eq!(id.to_string(), TEXT);
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let id = MiisCoreId::new(
+            Some((IdType::Virtual, [0x11; 16])),
+            None,
+            Some([0x22; 16]),
+            None,
+        );
+        let bytes = id.to_bytes();
+        assert_eq!(bytes[..2], [0x01, 0x88]);
+        assert_eq!(bytes.len(), 34);
+        assert_eq!(MiisCoreId::from_bytes(&bytes).unwrap(), id);
+    }
+
+    #[test]
+    fn missing_uuid() {
+        assert!(MiisCoreId::from_bytes(&[0x01, 0x70, 0x00]).is_err());
+        assert!("0170:F592".parse::<MiisCoreId>().is_err());
+    }
+}
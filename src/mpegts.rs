@@ -0,0 +1,905 @@
+//! Information from this page was gathered from ISO/IEC 13818-1 (MPEG-2
+//! Systems) and MISB ST 1402, "MPEG-2 Transport of Compressed Motion Imagery
+//! and Metadata".
+//!
+//! [`KlvDemuxer`] reads an MPEG-2 Transport Stream, finds the elementary
+//! streams that carry KLV metadata using the Program Map Tables, reassembles
+//! their PES packets and parses the UAS LS KLV packets they contain.
+//!
+//! A metadata elementary stream is one with a `stream_type` of `0x15`
+//! (metadata carried in PES packets) or `0x06` (private data in PES packets)
+//! that has a registration or metadata descriptor with the `KLVA` format
+//! identifier.
+//!
+//! MISB ST 1402 defines two ways of carrying the metadata. Synchronous
+//! metadata is sent in a metadata stream (`stream_id` `0xFC`) where each PES
+//! packet holds Metadata Access Unit cells and has a PTS tied to a video
+//! frame. Asynchronous metadata is sent in private stream 1 (`stream_id`
+//! `0xBD`) and is only loosely tied to the video by its arrival time, so its
+//! Precision Time Stamp has to be relied on instead. [`FrameAssociator`]
+//! matches packets of either kind to video frames.
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::VecDeque;
+use std::io::ErrorKind as IoErrorKind;
+use std::io::Read;
+
+use strum_macros::FromRepr;
+#[cfg(feature = "tracing")]
+use tracing::debug;
+#[cfg(feature = "tracing")]
+use tracing::trace;
+
+use crate::ErrorKind;
+use crate::klv_packet::KlvPacket;
+use crate::klv_packet::UAS_LOCAL_SET_UNIVERSAL_LABEL;
+use crate::klv_value::KlvValue;
+use crate::local_set::parse_triplet;
+use crate::tag::Tag;
+
+/// Size of a single transport stream packet.
+pub const TS_PACKET_SIZE: usize = 188;
+/// Every transport stream packet starts with this byte.
+const SYNC_BYTE: u8 = 0x47;
+/// PID of the Program Association Table.
+const PAT_PID: u16 = 0x0000;
+/// Metadata carried in PES packets.
+const STREAM_TYPE_METADATA: u8 = 0x15;
+/// Private data carried in PES packets.
+const STREAM_TYPE_PRIVATE: u8 = 0x06;
+/// Format identifier for SMPTE KLV metadata.
+const KLVA: &[u8; 4] = b"KLVA";
+/// PES `stream_id` of a metadata stream, used for synchronous metadata.
+const METADATA_STREAM_ID: u8 = 0xFC;
+/// PTS and PCR base values are 33 bits long and wrap around.
+const TIMESTAMP_MASK: u64 = (1 << 33) - 1;
+/// Largest PES packet or fragmented metadata cell that is reassembled. Larger
+/// ones are dropped, so a stream that never finishes a packet can't grow the
+/// buffers without limit.
+pub const MAX_PES_SIZE: usize = 1 << 20;
+
+/// How the metadata is carried in the transport stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetadataCarriage {
+    /// Carried in Metadata Access Units with a PTS tied to a video frame.
+    Synchronous,
+    /// Carried as private data without a relationship to any video frame.
+    Asynchronous,
+}
+
+/// Which part of a metadata cell is held by a [`MetadataAuCell`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, FromRepr)]
+#[repr(u8)]
+pub enum CellFragment {
+    Middle = 0b00,
+    Last = 0b01,
+    First = 0b10,
+    Complete = 0b11,
+}
+
+/// Header of a Metadata Access Unit cell from a synchronous metadata stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MetadataAuCell {
+    pub metadata_service_id: u8,
+    pub sequence_number: u8,
+    pub cell_fragment: CellFragment,
+    pub decoder_config: bool,
+    pub random_access: bool,
+}
+
+impl MetadataAuCell {
+    /// Parse the first cell of `bytes`, returning its header, its data and
+    /// the remaining bytes.
+    fn parse(bytes: &[u8]) -> Option<(MetadataAuCell, &[u8], &[u8])> {
+        let [service, sequence, flags, len_hi, len_lo, rest @ ..] = bytes
+        else {
+            return None;
+        };
+        let length = u16::from_be_bytes([*len_hi, *len_lo]) as usize;
+        let data = rest.get(..length)?;
+        let cell = MetadataAuCell {
+            metadata_service_id: *service,
+            sequence_number: *sequence,
+            cell_fragment: CellFragment::from_repr(flags >> 6)?,
+            decoder_config: flags & 0x20 != 0,
+            random_access: flags & 0x10 != 0,
+        };
+
+        Some((cell, data, &rest[length..]))
+    }
+}
+
+/// A KLV packet along with where and when it was found in the transport
+/// stream.
+#[derive(Clone, Debug)]
+pub struct TimedKlvPacket {
+    /// PID of the elementary stream the packet was carried in.
+    pub pid: u16,
+    /// Presentation time stamp of the PES packet, in 90kHz ticks.
+    pub pts: Option<u64>,
+    /// Base of the most recent PCR when the PES packet started, in 90kHz
+    /// ticks.
+    pub pcr: Option<u64>,
+    pub carriage: MetadataCarriage,
+    /// Header of the access unit cell holding the packet, for synchronous
+    /// metadata.
+    pub au_cell: Option<MetadataAuCell>,
+    /// The bytes of the KLV packet as they were received.
+    pub raw: Box<[u8]>,
+    pub packet: KlvPacket,
+}
+
+impl TimedKlvPacket {
+    /// Precision Time Stamp of the KLV packet, in microseconds since the
+    /// epoch.
+    pub fn timestamp(&self) -> Option<u64> {
+        match *self.packet.get(Tag::PrecisionTimeStamp)?.value() {
+            KlvValue::Uint64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The transport stream time of the packet, in 90kHz ticks. This is the
+    /// PTS if present or the PCR at which the packet arrived otherwise.
+    pub fn time(&self) -> Option<u64> {
+        self.pts.or(self.pcr)
+    }
+}
+
+/// `a` is at or before `b`, allowing for the 33 bit time stamps wrapping.
+fn at_or_before(a: u64, b: u64) -> bool {
+    b.wrapping_sub(a) & TIMESTAMP_MASK < 1 << 32
+}
+
+/// Associates metadata packets with video frames.
+///
+/// Packets are pushed in the order they were demuxed and frames are
+/// requested in presentation order. Synchronous packets only belong to the
+/// frame with the same PTS, and a frame may have several of them when more
+/// than one metadata service is carried. Asynchronous packets apply to every
+/// frame from their arrival until the next packet.
+#[derive(Clone, Debug, Default)]
+pub struct FrameAssociator {
+    pending: VecDeque<TimedKlvPacket>,
+    /// The most recent synchronous packets, which all have the same PTS.
+    synchronous: Vec<TimedKlvPacket>,
+    /// The most recent asynchronous packet, if no packet has arrived since.
+    asynchronous: Option<TimedKlvPacket>,
+}
+
+impl FrameAssociator {
+    pub fn new() -> FrameAssociator {
+        FrameAssociator::default()
+    }
+
+    /// Add a demuxed packet.
+    pub fn push(&mut self, packet: TimedKlvPacket) {
+        self.pending.push_back(packet);
+    }
+
+    /// Get the metadata for the video frame with the PTS `frame_pts`: every
+    /// synchronous packet with that PTS, in the order they were pushed, or
+    /// the asynchronous packet that applies to the frame.
+    ///
+    /// Packets older than the frame are discarded.
+    pub fn frame(
+        &mut self,
+        frame_pts: u64,
+    ) -> impl Iterator<Item = &TimedKlvPacket> + '_ {
+        while let Some(packet) = self.pending.front() {
+            if packet.time().is_some_and(|time| !at_or_before(time, frame_pts))
+            {
+                break;
+            }
+            let Some(packet) = self.pending.pop_front() else { break };
+            match packet.carriage {
+                MetadataCarriage::Synchronous => {
+                    self.synchronous.retain(|p| p.pts == packet.pts);
+                    self.synchronous.push(packet);
+                    self.asynchronous = None;
+                }
+                MetadataCarriage::Asynchronous => {
+                    self.synchronous.clear();
+                    self.asynchronous = Some(packet);
+                }
+            }
+        }
+
+        self.synchronous
+            .iter()
+            .filter(move |packet| packet.pts == Some(frame_pts))
+            .chain(&self.asynchronous)
+    }
+}
+
+/// A PES packet that is being reassembled.
+#[derive(Clone, Debug, Default)]
+struct PesBuffer {
+    data: Vec<u8>,
+    /// PCR at the start of the PES packet.
+    pcr: Option<u64>,
+    /// Data of a fragmented metadata cell.
+    fragment: Vec<u8>,
+}
+
+/// Demultiplexes KLV packets from transport stream data as it is received.
+///
+/// Data can be pushed in chunks of any size. [`KlvDemuxer`] wraps this for
+/// anything implementing [`Read`].
+#[derive(Debug, Default)]
+pub struct TsDemuxer {
+    /// Received data that does not yet make up a whole packet.
+    partial: Vec<u8>,
+    /// PIDs of Program Map Tables found in the Program Association Table.
+    pmt_pids: BTreeSet<u16>,
+    /// PIDs carrying the PCR of a program.
+    pcr_pids: BTreeSet<u16>,
+    /// Most recently seen PCR base.
+    pcr: Option<u64>,
+    /// PIDs of KLV metadata elementary streams and their reassembly buffers.
+    klv_pids: BTreeMap<u16, PesBuffer>,
+    /// Packets that have been parsed but not yet returned.
+    pending: VecDeque<Result<TimedKlvPacket, ErrorKind>>,
+}
+
+impl TsDemuxer {
+    /// Create a demuxer that discovers KLV streams from the Program Map
+    /// Tables.
+    pub fn new() -> TsDemuxer {
+        TsDemuxer::default()
+    }
+
+    /// Treat `pid` as a KLV metadata stream even if no Program Map Table
+    /// describes it.
+    pub fn add_pid(&mut self, pid: u16) {
+        self.klv_pids.entry(pid).or_default();
+    }
+
+    /// PIDs of the KLV metadata streams that are being read.
+    pub fn klv_pids(&self) -> impl Iterator<Item = u16> + '_ {
+        self.klv_pids.keys().copied()
+    }
+
+    /// Take the next KLV packet that has been demultiplexed.
+    pub fn pop(&mut self) -> Option<Result<TimedKlvPacket, ErrorKind>> {
+        self.pending.pop_front()
+    }
+
+    /// Feed received data to the demuxer, resynchronising on the sync byte
+    /// if needed.
+    pub fn push(&mut self, data: &[u8]) {
+        self.partial.extend_from_slice(data);
+
+        let mut offset = 0;
+        while let Some(packet) =
+            self.partial.get(offset..offset + TS_PACKET_SIZE)
+        {
+            let next = self.partial.get(offset + TS_PACKET_SIZE);
+            if packet[0] != SYNC_BYTE || next.is_some_and(|b| *b != SYNC_BYTE) {
+                #[cfg(feature = "tracing")]
+                trace!("Lost sync, searching for the next sync byte");
+                offset += Self::find_sync(&self.partial[offset..]);
+                continue;
+            }
+
+            let packet: [u8; TS_PACKET_SIZE] =
+                packet.try_into().expect("Slice is a whole packet");
+            self.handle_ts_packet(&packet);
+            offset += TS_PACKET_SIZE;
+        }
+
+        self.partial.drain(..offset);
+    }
+
+    /// Find the next position that looks like the start of a packet. A sync
+    /// byte is only trusted if the following packet starts with one too.
+    fn find_sync(data: &[u8]) -> usize {
+        (1..data.len())
+            .find(|idx| {
+                data[*idx] == SYNC_BYTE
+                    && data
+                        .get(idx + TS_PACKET_SIZE)
+                        .is_none_or(|next| *next == SYNC_BYTE)
+            })
+            .unwrap_or(data.len())
+    }
+
+    /// Handle a single transport stream packet.
+    fn handle_ts_packet(&mut self, packet: &[u8; TS_PACKET_SIZE]) {
+        let unit_start = packet[1] & 0x40 != 0;
+        let pid = u16::from_be_bytes([packet[1] & 0x1F, packet[2]]);
+        let adaptation_field_control = (packet[3] >> 4) & 0x03;
+
+        if adaptation_field_control & 0b10 != 0 && self.pcr_pids.contains(&pid)
+        {
+            self.handle_adaptation_field(packet);
+        }
+        let payload_start = match adaptation_field_control {
+            0b01 => 4,
+            0b11 => 5 + packet[4] as usize,
+            _ => return,
+        };
+        let Some(payload) = packet.get(payload_start..) else {
+            return;
+        };
+
+        if pid == PAT_PID {
+            if unit_start {
+                self.handle_pat(payload);
+            }
+        } else if self.pmt_pids.contains(&pid) {
+            if unit_start {
+                self.handle_pmt(payload);
+            }
+        } else if self.klv_pids.contains_key(&pid) {
+            self.handle_pes_payload(pid, unit_start, payload);
+        }
+    }
+
+    /// Record the PCR from the adaptation field of a packet, if present.
+    fn handle_adaptation_field(&mut self, packet: &[u8; TS_PACKET_SIZE]) {
+        let [_, _, _, _, length, flags, b0, b1, b2, b3, b4, ..] = *packet;
+        if length >= 7 && flags & 0x10 != 0 {
+            self.pcr = Some(
+                (b0 as u64) << 25
+                    | (b1 as u64) << 17
+                    | (b2 as u64) << 9
+                    | (b3 as u64) << 1
+                    | b4 as u64 >> 7,
+            );
+        }
+    }
+
+    /// Get the section from a PSI payload, skipping the pointer field.
+    fn section(payload: &[u8]) -> Option<&[u8]> {
+        let pointer = *payload.first()? as usize;
+        let section = payload.get(1 + pointer..)?;
+        let length = ((section.get(1)? & 0x0F) as usize) << 8
+            | *section.get(2)? as usize;
+        // Exclude the CRC from the end of the section.
+        section.get(..(3 + length).checked_sub(4)?)
+    }
+
+    fn handle_pat(&mut self, payload: &[u8]) {
+        let Some(section) = Self::section(payload) else {
+            return;
+        };
+        let Some(programs) = section.get(8..) else {
+            return;
+        };
+
+        for program in programs.chunks_exact(4) {
+            let number = u16::from_be_bytes([program[0], program[1]]);
+            let pid = u16::from_be_bytes([program[2] & 0x1F, program[3]]);
+            // Program number 0 is the network PID rather than a PMT.
+            if number != 0 && self.pmt_pids.insert(pid) {
+                #[cfg(feature = "tracing")]
+                debug!("Found PMT on PID [{}]", pid);
+            }
+        }
+    }
+
+    fn handle_pmt(&mut self, payload: &[u8]) {
+        let Some(section) = Self::section(payload) else {
+            return;
+        };
+        if let Some(pcr) = section.get(8..10) {
+            self.pcr_pids.insert(u16::from_be_bytes([pcr[0] & 0x1F, pcr[1]]));
+        }
+        let Some(info_length) = section
+            .get(10..12)
+            .map(|l| ((l[0] & 0x0F) as usize) << 8 | l[1] as usize)
+        else {
+            return;
+        };
+
+        let mut streams = section.get(12 + info_length..).unwrap_or_default();
+        while let [stream_type, pid_hi, pid_lo, len_hi, len_lo, rest @ ..] =
+            streams
+        {
+            let pid = u16::from_be_bytes([pid_hi & 0x1F, *pid_lo]);
+            let length = ((len_hi & 0x0F) as usize) << 8 | *len_lo as usize;
+            let Some(descriptors) = rest.get(..length) else {
+                return;
+            };
+
+            let is_pes = matches!(
+                *stream_type,
+                STREAM_TYPE_METADATA | STREAM_TYPE_PRIVATE
+            );
+            if is_pes
+                && Self::has_klva(descriptors)
+                && !self.klv_pids.contains_key(&pid)
+            {
+                #[cfg(feature = "tracing")]
+                debug!("Found KLV metadata stream on PID [{}]", pid);
+                self.add_pid(pid);
+            }
+
+            streams = &rest[length..];
+        }
+    }
+
+    /// Check if any registration (0x05) or metadata (0x26) descriptor
+    /// identifies the stream as KLV.
+    fn has_klva(mut descriptors: &[u8]) -> bool {
+        while let [tag, length, rest @ ..] = descriptors {
+            let Some(data) = rest.get(..*length as usize) else {
+                return false;
+            };
+            if matches!(tag, 0x05 | 0x26)
+                && data.windows(KLVA.len()).any(|w| w == KLVA)
+            {
+                return true;
+            }
+            descriptors = &rest[*length as usize..];
+        }
+        false
+    }
+
+    fn handle_pes_payload(
+        &mut self,
+        pid: u16,
+        unit_start: bool,
+        payload: &[u8],
+    ) {
+        let Some(buffer) = self.klv_pids.get_mut(&pid) else {
+            return;
+        };
+
+        if unit_start {
+            let previous = std::mem::take(&mut buffer.data);
+            let pcr = std::mem::replace(&mut buffer.pcr, self.pcr);
+            buffer.data.extend_from_slice(payload);
+            self.handle_pes(pid, pcr, &previous);
+        } else if buffer.data.len() + payload.len() > MAX_PES_SIZE {
+            #[cfg(feature = "tracing")]
+            debug!("Dropping oversized PES packet on PID [{}]", pid);
+            buffer.data.clear();
+        } else if !buffer.data.is_empty() {
+            buffer.data.extend_from_slice(payload);
+        }
+
+        // Packets with a known length can be handled as soon as they are
+        // complete rather than waiting for the next one to start.
+        let Some(buffer) = self.klv_pids.get_mut(&pid) else {
+            return;
+        };
+        let Some(length) = Self::pes_length(&buffer.data) else {
+            return;
+        };
+        if buffer.data.len() >= length {
+            let data = std::mem::take(&mut buffer.data);
+            let pcr = buffer.pcr;
+            self.handle_pes(pid, pcr, &data[..length]);
+        }
+    }
+
+    /// Total length of the PES packet, if it is specified by the header.
+    fn pes_length(data: &[u8]) -> Option<usize> {
+        let length = u16::from_be_bytes([*data.get(4)?, *data.get(5)?]);
+        (length != 0).then_some(6 + length as usize)
+    }
+
+    /// Parse a complete PES packet into KLV packets.
+    fn handle_pes(&mut self, pid: u16, pcr: Option<u64>, data: &[u8]) {
+        let Some((stream_id, pts, payload)) = parse_pes(data) else {
+            return;
+        };
+        let packet = |carriage, au_cell| {
+            move |(packet, raw): (KlvPacket, &[u8])| TimedKlvPacket {
+                pid,
+                pts,
+                pcr,
+                carriage,
+                au_cell,
+                raw: raw.into(),
+                packet,
+            }
+        };
+
+        if stream_id != METADATA_STREAM_ID {
+            let packet = packet(MetadataCarriage::Asynchronous, None);
+            for result in split_klv(payload) {
+                self.pending.push_back(result.map(packet));
+            }
+            return;
+        }
+
+        let mut cells = payload;
+        while let Some((cell, data, rest)) = MetadataAuCell::parse(cells) {
+            cells = rest;
+
+            let assembled;
+            let data = if cell.cell_fragment == CellFragment::Complete {
+                data
+            } else {
+                let Some(buffer) = self.klv_pids.get_mut(&pid) else {
+                    return;
+                };
+                if cell.cell_fragment == CellFragment::First
+                    || buffer.fragment.len() + data.len() > MAX_PES_SIZE
+                {
+                    buffer.fragment.clear();
+                }
+                buffer.fragment.extend_from_slice(data);
+                if cell.cell_fragment != CellFragment::Last {
+                    continue;
+                }
+                assembled = std::mem::take(&mut buffer.fragment);
+                &assembled
+            };
+
+            let packet = packet(MetadataCarriage::Synchronous, Some(cell));
+            for result in split_klv(data) {
+                self.pending.push_back(result.map(packet));
+            }
+        }
+    }
+
+    /// Handle any PES packets that are still being reassembled, once the end
+    /// of the stream has been reached.
+    pub fn finish(&mut self) {
+        let pids: Vec<u16> = self.klv_pids.keys().copied().collect();
+        for pid in pids {
+            let Some(buffer) = self.klv_pids.get_mut(&pid) else {
+                continue;
+            };
+            let data = std::mem::take(&mut buffer.data);
+            let pcr = buffer.pcr;
+            self.handle_pes(pid, pcr, &data);
+        }
+    }
+}
+
+/// Reads KLV packets out of an MPEG-2 Transport Stream.
+pub struct KlvDemuxer<R: Read> {
+    reader: R,
+    demuxer: TsDemuxer,
+    finished: bool,
+}
+
+impl<R: Read> KlvDemuxer<R> {
+    /// Create a demuxer that discovers KLV streams from the Program Map
+    /// Tables.
+    pub fn new(reader: R) -> KlvDemuxer<R> {
+        KlvDemuxer { reader, demuxer: TsDemuxer::new(), finished: false }
+    }
+
+    /// Treat `pid` as a KLV metadata stream even if no Program Map Table
+    /// describes it.
+    pub fn add_pid(&mut self, pid: u16) {
+        self.demuxer.add_pid(pid);
+    }
+
+    /// PIDs of the KLV metadata streams that are being read.
+    pub fn klv_pids(&self) -> impl Iterator<Item = u16> + '_ {
+        self.demuxer.klv_pids()
+    }
+}
+
+impl<R: Read> Iterator for KlvDemuxer<R> {
+    type Item = Result<TimedKlvPacket, ErrorKind>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buf = [0; TS_PACKET_SIZE * 7];
+        loop {
+            if let Some(packet) = self.demuxer.pop() {
+                return Some(packet);
+            }
+            if self.finished {
+                return None;
+            }
+
+            match self.reader.read(&mut buf) {
+                Ok(0) => {
+                    self.finished = true;
+                    self.demuxer.finish();
+                }
+                Ok(length) => self.demuxer.push(&buf[..length]),
+                Err(e) if e.kind() == IoErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(ErrorKind::Io(e)));
+                }
+            }
+        }
+    }
+}
+
+/// Parse the header of a PES packet, returning its stream ID, PTS and
+/// payload.
+pub(crate) fn parse_pes(data: &[u8]) -> Option<(u8, Option<u64>, &[u8])> {
+    let [0x00, 0x00, 0x01, stream_id, _, _, _, flags, header_length, rest @ ..] =
+        data
+    else {
+        return None;
+    };
+
+    let header = rest.get(..*header_length as usize)?;
+    let payload = &rest[*header_length as usize..];
+
+    // The PTS is present if the first PTS_DTS flag is set.
+    let pts = if flags & 0x80 != 0 {
+        let [b0, b1, b2, b3, b4, ..] = header else {
+            return None;
+        };
+        Some(
+            ((*b0 as u64 >> 1) & 0x07) << 30
+                | (*b1 as u64) << 22
+                | (*b2 as u64 >> 1) << 15
+                | (*b3 as u64) << 7
+                | *b4 as u64 >> 1,
+        )
+    } else {
+        None
+    };
+
+    Some((*stream_id, pts, payload))
+}
+
+/// Split a buffer containing one or more consecutive KLV triplets into the
+/// UAS LS packets it contains, along with their bytes. Triplets with other
+/// keys are skipped.
+pub(crate) fn split_klv(
+    mut data: &[u8],
+) -> impl Iterator<Item = Result<(KlvPacket, &[u8]), ErrorKind>> + '_ {
+    std::iter::from_fn(move || {
+        loop {
+            let (key, _, length) = parse_triplet(data).ok()?;
+            let triplet = &data[..length];
+            data = &data[length..];
+
+            if key == UAS_LOCAL_SET_UNIVERSAL_LABEL {
+                return KlvPacket::from_bytes(triplet)
+                    .transpose()
+                    .map(|packet| packet.map(|packet| (packet, triplet)));
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::Cursor;
+
+    use super::CellFragment;
+    use super::FrameAssociator;
+    use super::KlvDemuxer;
+    use super::MAX_PES_SIZE;
+    use super::MetadataCarriage;
+    use super::TS_PACKET_SIZE;
+    use super::TimedKlvPacket;
+    use super::TsDemuxer;
+    use crate::klv_packet::UAS_LOCAL_SET_UNIVERSAL_LABEL;
+    use crate::local_set::ChecksumRule;
+
+    /// Build a UAS LS packet containing only a precision time stamp.
+    pub(crate) fn klv_packet(timestamp: u64) -> Vec<u8> {
+        let mut bytes = Vec::from(UAS_LOCAL_SET_UNIVERSAL_LABEL);
+        bytes.push(14);
+        bytes.extend_from_slice(&[0x02, 0x08]);
+        bytes.extend_from_slice(&timestamp.to_be_bytes());
+        bytes.extend_from_slice(&[0x01, 0x02]);
+        let checksum = ChecksumRule::Bcc16 { tag: 1 }.calculate(&bytes);
+        bytes.extend_from_slice(&checksum.unwrap().to_be_bytes());
+        bytes
+    }
+
+    /// Build a transport stream packet, padding the payload with an
+    /// adaptation field.
+    pub(crate) fn ts_packet(
+        pid: u16,
+        unit_start: bool,
+        payload: &[u8],
+    ) -> Vec<u8> {
+        let mut packet = vec![
+            0x47,
+            ((pid >> 8) as u8 & 0x1F) | if unit_start { 0x40 } else { 0 },
+            pid as u8,
+        ];
+        let stuffing = TS_PACKET_SIZE - 4 - payload.len();
+        if stuffing > 0 {
+            packet.push(0x30);
+            packet.push(stuffing as u8 - 1);
+            if stuffing > 1 {
+                packet.push(0x00);
+                packet.extend(std::iter::repeat_n(0xFF, stuffing - 2));
+            }
+        } else {
+            packet.push(0x10);
+        }
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    /// Build a PSI section payload with a pointer field and dummy CRC.
+    fn section(table_id: u8, body: &[u8]) -> Vec<u8> {
+        let length = body.len() + 5 + 4;
+        let mut section = vec![
+            0x00,
+            table_id,
+            0xB0 | (length >> 8) as u8,
+            length as u8,
+            0x00,
+            0x01,
+            0xC1,
+            0x00,
+            0x00,
+        ];
+        section.extend_from_slice(body);
+        section.extend_from_slice(&[0; 4]);
+        section
+    }
+
+    pub(crate) fn pat_and_pmt(klv_pid: u16) -> Vec<u8> {
+        let pat = section(0x00, &[0x00, 0x01, 0xE1, 0x00]);
+        let mut pmt_body = vec![0xE1, 0x01, 0xF0, 0x00];
+        pmt_body.extend_from_slice(&[
+            0x15,
+            0xE0 | (klv_pid >> 8) as u8,
+            klv_pid as u8,
+            0xF0,
+            0x06,
+            0x05,
+            0x04,
+        ]);
+        pmt_body.extend_from_slice(b"KLVA");
+        let pmt = section(0x02, &pmt_body);
+
+        let mut stream = ts_packet(0x0000, true, &pat);
+        stream.extend(ts_packet(0x0100, true, &pmt));
+        stream
+    }
+
+    pub(crate) fn pes(stream_id: u8, pts: u64, payload: &[u8]) -> Vec<u8> {
+        let length = 3 + 5 + payload.len();
+        let mut pes = vec![
+            0x00,
+            0x00,
+            0x01,
+            stream_id,
+            (length >> 8) as u8,
+            length as u8,
+            0x80,
+            0x80,
+            0x05,
+            0x21 | ((pts >> 29) as u8 & 0x0E),
+            (pts >> 22) as u8,
+            0x01 | (pts >> 14) as u8,
+            (pts >> 7) as u8,
+            0x01 | (pts << 1) as u8,
+        ];
+        pes.extend_from_slice(payload);
+        pes
+    }
+
+    #[test]
+    fn demux() {
+        let mut payload = klv_packet(1);
+        payload.extend(klv_packet(2));
+        let pes = pes(0xBD, 900_000, &payload);
+
+        let mut stream = vec![0x00, 0x12];
+        stream.extend(pat_and_pmt(0x0102));
+        stream.extend(ts_packet(0x0102, true, &pes[..40]));
+        stream.extend(ts_packet(0x0102, false, &pes[40..]));
+
+        let mut demuxer = KlvDemuxer::new(Cursor::new(stream));
+        let packets: Vec<_> = demuxer.by_ref().map(|p| p.unwrap()).collect();
+        assert_eq!(demuxer.klv_pids().collect::<Vec<_>>(), vec![0x0102]);
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0].pts, Some(900_000));
+        assert_eq!(packets[0].carriage, MetadataCarriage::Asynchronous);
+        assert_eq!(packets[1].timestamp(), Some(2));
+    }
+
+    #[test]
+    fn oversized_pes() {
+        let mut demuxer = TsDemuxer::new();
+        demuxer.add_pid(0x0102);
+        // A PES packet without a length that never ends.
+        let header = [0x00, 0x00, 0x01, 0xBD, 0x00, 0x00, 0x80, 0x00, 0x00];
+        demuxer.push(&ts_packet(0x0102, true, &header));
+        for _ in 0..MAX_PES_SIZE / 184 + 1 {
+            demuxer.push(&ts_packet(0x0102, false, &[0xFF; 184]));
+        }
+        assert!(demuxer.klv_pids[&0x0102].data.len() <= MAX_PES_SIZE);
+
+        demuxer.push(&ts_packet(0x0102, true, &pes(0xBD, 0, &klv_packet(1))));
+        demuxer.finish();
+        assert_eq!(demuxer.pop().unwrap().unwrap().timestamp(), Some(1));
+    }
+
+    /// Build a metadata access unit cell.
+    fn au_cell(sequence: u8, fragment: CellFragment, data: &[u8]) -> Vec<u8> {
+        let mut cell = vec![0x00, sequence, (fragment as u8) << 6 | 0x0F];
+        cell.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        cell.extend_from_slice(data);
+        cell
+    }
+
+    #[test]
+    fn synchronous() {
+        let second = klv_packet(2);
+        let mut cells = au_cell(0, CellFragment::Complete, &klv_packet(1));
+        cells.extend(au_cell(1, CellFragment::First, &second[..10]));
+        cells.extend(au_cell(1, CellFragment::Last, &second[10..]));
+
+        let mut stream = pat_and_pmt(0x0102);
+        stream.extend(ts_packet(0x0102, true, &pes(0xFC, 3003, &cells)));
+
+        let packets: Vec<_> =
+            KlvDemuxer::new(Cursor::new(stream)).map(|p| p.unwrap()).collect();
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0].carriage, MetadataCarriage::Synchronous);
+        assert_eq!(packets[1].au_cell.unwrap().sequence_number, 1);
+        assert_eq!(packets[1].timestamp(), Some(2));
+        assert_eq!(packets[1].pts, Some(3003));
+    }
+
+    fn timed(
+        pts: u64,
+        carriage: MetadataCarriage,
+        timestamp: u64,
+    ) -> TimedKlvPacket {
+        let bytes = klv_packet(timestamp);
+        TimedKlvPacket {
+            pid: 0x0102,
+            pts: Some(pts),
+            pcr: None,
+            carriage,
+            au_cell: None,
+            raw: bytes.clone().into(),
+            packet: crate::klv_packet::KlvPacket::from_bytes(&bytes)
+                .unwrap()
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn frame_association() {
+        let mut associator = FrameAssociator::new();
+        associator.push(timed(3000, MetadataCarriage::Synchronous, 1));
+        associator.push(timed(9000, MetadataCarriage::Synchronous, 2));
+        associator.push(timed(10000, MetadataCarriage::Asynchronous, 3));
+
+        let mut timestamps = |pts| {
+            associator
+                .frame(pts)
+                .filter_map(TimedKlvPacket::timestamp)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(timestamps(0), []);
+        assert_eq!(timestamps(3000), [1]);
+        assert_eq!(timestamps(6000), []);
+        assert_eq!(timestamps(9000), [2]);
+        assert_eq!(timestamps(12000), [3]);
+        assert_eq!(timestamps(15000), [3]);
+    }
+
+    #[test]
+    fn frame_with_several_services() {
+        // Cells from two metadata services in one PES packet.
+        let mut cells = au_cell(0, CellFragment::Complete, &klv_packet(1));
+        let mut second = au_cell(0, CellFragment::Complete, &klv_packet(2));
+        second[0] = 1;
+        cells.extend(second);
+
+        let mut stream = pat_and_pmt(0x0102);
+        stream.extend(ts_packet(0x0102, true, &pes(0xFC, 3003, &cells)));
+
+        let mut associator = FrameAssociator::new();
+        for packet in KlvDemuxer::new(Cursor::new(stream)) {
+            associator.push(packet.unwrap());
+        }
+        let timestamps: Vec<_> = associator
+            .frame(3003)
+            .filter_map(TimedKlvPacket::timestamp)
+            .collect();
+        assert_eq!(timestamps, [1, 2]);
+        // Asking again returns the same packets.
+        assert_eq!(associator.frame(3003).count(), 2);
+        assert_eq!(associator.frame(6006).count(), 0);
+    }
+}
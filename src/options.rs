@@ -0,0 +1,125 @@
+//! Options controlling how strictly packets are parsed.
+//!
+//! The `search` and `ignore_incomplete` features only choose the defaults of
+//! [`ParseOptions`]. Every option can be changed at runtime, so different
+//! parts of one program can parse the same data with different policies.
+use crate::local_set::ChecksumPolicy;
+
+/// What to do with a tag that isn't defined, or is deprecated, in MISB ST
+/// 0601.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownTagPolicy {
+    /// Reject the packet with [`ErrorKind::UnsupportedTag`].
+    ///
+    /// [`ErrorKind::UnsupportedTag`]: crate::ErrorKind::UnsupportedTag
+    #[default]
+    Reject,
+    /// Leave the field out of the decoded packet.
+    Skip,
+}
+
+/// What to do with a value whose type can't be decoded yet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnimplementedPolicy {
+    /// Reject the packet with [`ErrorKind::Unimplemented`].
+    ///
+    /// [`ErrorKind::Unimplemented`]: crate::ErrorKind::Unimplemented
+    Reject,
+    /// Keep the field with a value of [`KlvValue::Unimplemented`].
+    ///
+    /// [`KlvValue::Unimplemented`]: crate::klv_value::KlvValue::Unimplemented
+    Keep,
+    /// Leave the field out of the decoded packet.
+    Skip,
+}
+
+impl Default for UnimplementedPolicy {
+    fn default() -> Self {
+        if cfg!(feature = "ignore_incomplete") {
+            UnimplementedPolicy::Keep
+        } else {
+            UnimplementedPolicy::Reject
+        }
+    }
+}
+
+/// What to do with a value whose length isn't allowed for its tag, such as a
+/// [`Tag::SensorLatitude`] that is two bytes rather than four.
+///
+/// [`Tag::SensorLatitude`]: crate::tag::Tag::SensorLatitude
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LengthPolicy {
+    /// Reject the packet with [`ErrorKind::InvalidValueLength`].
+    ///
+    /// [`ErrorKind::InvalidValueLength`]: crate::ErrorKind::InvalidValueLength
+    #[default]
+    Reject,
+    /// Leave the field out of the decoded packet.
+    Skip,
+    /// Decode the value at the length it was sent, as long as it fits in the
+    /// type of the tag. Signed integers are sign extended. Values longer than
+    /// their type still reject the packet.
+    Keep,
+}
+
+/// Which field is used when a tag appears more than once in a packet.
+///
+/// MISB ST 0601 doesn't allow tags to repeat, but some encoders repeat a tag
+/// to correct a value sent earlier in the packet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Use the first occurrence.
+    #[default]
+    First,
+    /// Use the last occurrence.
+    Last,
+    /// Reject the packet with [`ErrorKind::DuplicateTag`].
+    ///
+    /// [`ErrorKind::DuplicateTag`]: crate::ErrorKind::DuplicateTag
+    Reject,
+}
+
+/// Options for [`KlvPacket::parse_with`].
+///
+/// [`KlvPacket::parse_with`]: crate::klv_packet::KlvPacket::parse_with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Look for the universal key anywhere in the input rather than only at
+    /// the start. Defaults to whether the `search` feature is enabled.
+    pub search: bool,
+    pub unknown_tags: UnknownTagPolicy,
+    pub checksum: ChecksumPolicy,
+    /// Defaults to [`UnimplementedPolicy::Keep`] if the `ignore_incomplete`
+    /// feature is enabled and [`UnimplementedPolicy::Reject`] otherwise.
+    pub unimplemented: UnimplementedPolicy,
+    pub invalid_lengths: LengthPolicy,
+    /// Which occurrence of a repeated tag [`KlvPacket::get`] and the typed
+    /// accessors return. Every occurrence is kept in the packet.
+    ///
+    /// [`KlvPacket::get`]: crate::klv_packet::KlvPacket::get
+    pub duplicates: DuplicatePolicy,
+    /// Largest packet accepted, in bytes from the start of the universal key
+    /// to the end of the value.
+    ///
+    /// Packets that declare a larger length are rejected before any of their
+    /// fields are read.
+    pub max_packet_size: usize,
+    /// Deepest nesting of local sets accepted. The packet itself has a
+    /// depth of one, so a depth of one rejects packets containing local sets.
+    pub max_depth: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            search: cfg!(feature = "search"),
+            unknown_tags: UnknownTagPolicy::default(),
+            checksum: ChecksumPolicy::default(),
+            unimplemented: UnimplementedPolicy::default(),
+            invalid_lengths: LengthPolicy::default(),
+            duplicates: DuplicatePolicy::default(),
+            max_packet_size: usize::MAX,
+            max_depth: 8,
+        }
+    }
+}
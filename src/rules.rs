@@ -0,0 +1,517 @@
+//! Rules for finding the parts of a recording where the metadata meets some
+//! condition, such as the laser being on or a weapon being fired.
+//!
+//! Rules are usually loaded from a [`RuleSet`] file with one rule per line:
+//!
+//! ```text
+//! # name = condition [for SECONDS]
+//! laser = laser_on for 2
+//! strike = weapon_fired
+//! alpha = mission_id "ALPHA 1" and not has_tag SensorLatitude
+//! area = frame_center_within 45.0 -90.0, 45.1 -90.0, 45.1 -89.9
+//! ```
+//!
+//! Conditions are combined with `and`, `or`, `not` and parentheses. The
+//! optional `for` clause is the shortest time, in seconds, that the condition
+//! must hold for before it is reported.
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::iter::Peekable;
+use core::str::FromStr;
+
+use crate::ErrorKind;
+use crate::klv_packet::KlvPacket;
+use crate::klv_value::KlvValue;
+use crate::tag::Tag;
+
+/// A test applied to a single packet.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    /// The laser on bit of Generic Flag Data is set.
+    LaserOn,
+    /// The packet reports a weapon release.
+    WeaponFired,
+    /// The Mission ID is exactly this string.
+    MissionId(Arc<str>),
+    /// The packet contains the tag.
+    HasTag(Tag),
+    /// The frame center is inside the polygon of `(latitude, longitude)`
+    /// points, in degrees.
+    FrameCenterWithin(Vec<(f64, f64)>),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    pub fn matches(&self, packet: &KlvPacket) -> bool {
+        match self {
+            Condition::LaserOn => {
+                packet.generic_flags().is_some_and(|flags| flags.laser_on())
+            }
+            Condition::WeaponFired => packet.weapon_fired().is_some(),
+            Condition::MissionId(id) => {
+                packet.mission_id().is_some_and(|mission| *mission == **id)
+            }
+            Condition::HasTag(tag) => packet.get_ref(*tag).is_some(),
+            Condition::FrameCenterWithin(polygon) => packet
+                .frame_center()
+                .is_some_and(|point| contains(polygon, point)),
+            Condition::All(conditions) => {
+                conditions.iter().all(|condition| condition.matches(packet))
+            }
+            Condition::Any(conditions) => {
+                conditions.iter().any(|condition| condition.matches(packet))
+            }
+            Condition::Not(condition) => !condition.matches(packet),
+        }
+    }
+}
+
+/// Whether `point` is inside `polygon`, using the even-odd rule.
+fn contains(polygon: &[(f64, f64)], (y, x): (f64, f64)) -> bool {
+    let mut inside = false;
+    let edges = polygon.iter().zip(polygon.iter().cycle().skip(1));
+    for (&(y1, x1), &(y2, x2)) in edges {
+        if (y1 > y) != (y2 > y) && x < (x2 - x1) * (y - y1) / (y2 - y1) + x1 {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// A named condition and how long it must hold for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rule {
+    pub name: Arc<str>,
+    pub condition: Condition,
+    /// Shortest interval to report, in microseconds.
+    pub min_duration: u64,
+}
+
+/// A span of time between two precision time stamps, inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Interval {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Interval {
+    /// Length of the interval in microseconds.
+    pub fn duration(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+/// The intervals in which a rule held.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleMatch {
+    pub rule: Arc<str>,
+    pub intervals: Vec<Interval>,
+}
+
+/// An ordered list of rules.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RuleSet {
+    pub rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    pub fn new(rules: Vec<Rule>) -> RuleSet {
+        RuleSet { rules }
+    }
+
+    /// Start evaluating the rules over a sequence of packets.
+    pub fn evaluator(&self) -> Evaluator<'_> {
+        Evaluator {
+            rules: &self.rules,
+            runs: alloc::vec![None; self.rules.len()],
+            intervals: alloc::vec![Vec::new(); self.rules.len()],
+        }
+    }
+
+    /// Evaluate the rules over `packets`, returning the rules that held for
+    /// long enough in the order they were defined.
+    pub fn evaluate<'a>(
+        &self,
+        packets: impl IntoIterator<Item = &'a KlvPacket>,
+    ) -> Vec<RuleMatch> {
+        let mut evaluator = self.evaluator();
+        for packet in packets {
+            evaluator.push(packet);
+        }
+        evaluator.finish()
+    }
+}
+
+impl FromStr for RuleSet {
+    type Err = ErrorKind;
+
+    fn from_str(s: &str) -> Result<RuleSet, ErrorKind> {
+        let mut rules = Vec::new();
+        for (idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let rule = line.parse::<Rule>().map_err(|e| match e {
+                ErrorKind::InvalidRule(reason) => ErrorKind::InvalidRule(
+                    format!("line {}: {}", idx + 1, reason),
+                ),
+                e => e,
+            })?;
+            rules.push(rule);
+        }
+        Ok(RuleSet { rules })
+    }
+}
+
+/// Evaluates a [`RuleSet`] one packet at a time.
+///
+/// Consecutive packets that meet a rule form an interval from the first to
+/// the last of them. Packets without a precision time stamp are ignored.
+#[derive(Clone, Debug)]
+pub struct Evaluator<'a> {
+    rules: &'a [Rule],
+    runs: Vec<Option<Interval>>,
+    intervals: Vec<Vec<Interval>>,
+}
+
+impl Evaluator<'_> {
+    pub fn push(&mut self, packet: &KlvPacket) {
+        let Some(KlvValue::Uint64(timestamp)) =
+            packet.get_ref(Tag::PrecisionTimeStamp).map(|field| field.value())
+        else {
+            return;
+        };
+
+        for (idx, rule) in self.rules.iter().enumerate() {
+            if rule.condition.matches(packet) {
+                let run = self.runs[idx]
+                    .get_or_insert(Interval { start: *timestamp, end: 0 });
+                run.end = *timestamp;
+            } else {
+                self.close(idx);
+            }
+        }
+    }
+
+    fn close(&mut self, idx: usize) {
+        if let Some(run) = self.runs[idx].take() {
+            if run.duration() >= self.rules[idx].min_duration {
+                self.intervals[idx].push(run);
+            }
+        }
+    }
+
+    /// Finish evaluating, returning the rules that held for long enough in
+    /// the order they were defined.
+    pub fn finish(mut self) -> Vec<RuleMatch> {
+        for idx in 0..self.rules.len() {
+            self.close(idx);
+        }
+        self.rules
+            .iter()
+            .zip(self.intervals)
+            .filter(|(_, intervals)| !intervals.is_empty())
+            .map(|(rule, intervals)| RuleMatch {
+                rule: rule.name.clone(),
+                intervals,
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ErrorKind;
+
+    fn from_str(s: &str) -> Result<Rule, ErrorKind> {
+        let invalid = |reason: &str| ErrorKind::InvalidRule(reason.to_string());
+
+        let (name, definition) = s
+            .split_once('=')
+            .ok_or_else(|| invalid("expected `name = ...`"))?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(invalid("missing rule name"));
+        }
+
+        let mut tokens = tokenize(definition)?.into_iter().peekable();
+        let condition = parse_or(&mut tokens)?;
+
+        let min_duration = match tokens.next() {
+            None => 0,
+            Some(Token::Word(word)) if word == "for" => {
+                let seconds = match tokens.next() {
+                    Some(Token::Word(seconds)) => seconds
+                        .trim_end_matches('s')
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|seconds| *seconds >= 0.0),
+                    _ => None,
+                }
+                .ok_or_else(|| invalid("expected a duration after `for`"))?;
+                (seconds * 1_000_000.0) as u64
+            }
+            Some(token) => {
+                return Err(invalid(&format!("unexpected `{}`", token)));
+            }
+        };
+        if let Some(token) = tokens.next() {
+            return Err(invalid(&format!("unexpected `{}`", token)));
+        }
+
+        Ok(Rule { name: name.into(), condition, min_duration })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Open,
+    Close,
+    Comma,
+}
+
+impl core::fmt::Display for Token {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "{}", word),
+            Token::Quoted(text) => write!(f, "\"{}\"", text),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, ErrorKind> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            ',' => tokens.push(Token::Comma),
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => text.push(c),
+                        None => {
+                            return Err(ErrorKind::InvalidRule(
+                                "unterminated string".to_string(),
+                            ));
+                        }
+                    }
+                }
+                tokens.push(Token::Quoted(text));
+            }
+            c => {
+                let mut word = String::from(c);
+                while let Some(c) = chars.next_if(|c| {
+                    !c.is_whitespace() && !matches!(c, '(' | ')' | ',' | '"')
+                }) {
+                    word.push(c);
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+type Tokens = Peekable<alloc::vec::IntoIter<Token>>;
+
+fn keyword(tokens: &mut Tokens, keyword: &str) -> bool {
+    tokens
+        .next_if(|token| matches!(token, Token::Word(w) if w == keyword))
+        .is_some()
+}
+
+fn parse_or(tokens: &mut Tokens) -> Result<Condition, ErrorKind> {
+    let mut conditions = alloc::vec![parse_and(tokens)?];
+    while keyword(tokens, "or") {
+        conditions.push(parse_and(tokens)?);
+    }
+    Ok(match conditions.len() {
+        1 => conditions.remove(0),
+        _ => Condition::Any(conditions),
+    })
+}
+
+fn parse_and(tokens: &mut Tokens) -> Result<Condition, ErrorKind> {
+    let mut conditions = alloc::vec![parse_not(tokens)?];
+    while keyword(tokens, "and") {
+        conditions.push(parse_not(tokens)?);
+    }
+    Ok(match conditions.len() {
+        1 => conditions.remove(0),
+        _ => Condition::All(conditions),
+    })
+}
+
+fn parse_not(tokens: &mut Tokens) -> Result<Condition, ErrorKind> {
+    let invalid = |reason: String| ErrorKind::InvalidRule(reason);
+
+    let word = match tokens.next() {
+        Some(Token::Word(word)) => word,
+        Some(Token::Open) => {
+            let condition = parse_or(tokens)?;
+            return match tokens.next() {
+                Some(Token::Close) => Ok(condition),
+                _ => Err(invalid("expected `)`".to_string())),
+            };
+        }
+        Some(token) => return Err(invalid(format!("unexpected `{}`", token))),
+        None => return Err(invalid("expected a condition".to_string())),
+    };
+
+    match word.as_str() {
+        "not" => Ok(Condition::Not(Box::new(parse_not(tokens)?))),
+        "laser_on" => Ok(Condition::LaserOn),
+        "weapon_fired" => Ok(Condition::WeaponFired),
+        "mission_id" => match tokens.next() {
+            Some(Token::Quoted(id) | Token::Word(id)) => {
+                Ok(Condition::MissionId(id.into()))
+            }
+            _ => Err(invalid("expected a mission ID".to_string())),
+        },
+        "has_tag" => {
+            let tag = match tokens.next() {
+                Some(Token::Word(tag)) => {
+                    tag.parse::<Tag>().ok().filter(|tag| {
+                        !matches!(*tag, Tag::Unknown | Tag::Deprecated)
+                    })
+                }
+                _ => None,
+            };
+            Ok(Condition::HasTag(
+                tag.ok_or_else(|| invalid("expected a tag".to_string()))?,
+            ))
+        }
+        "frame_center_within" => {
+            let mut polygon = Vec::new();
+            loop {
+                let mut coordinate = || match tokens.next() {
+                    Some(Token::Word(value)) => value.parse::<f64>().ok(),
+                    _ => None,
+                };
+                let point =
+                    coordinate().zip(coordinate()).ok_or_else(|| {
+                        invalid("expected a latitude and longitude".to_string())
+                    })?;
+                polygon.push(point);
+                if tokens.next_if_eq(&Token::Comma).is_none() {
+                    break;
+                }
+            }
+            if polygon.len() < 3 {
+                return Err(invalid(
+                    "a polygon needs at least 3 points".to_string(),
+                ));
+            }
+            Ok(Condition::FrameCenterWithin(polygon))
+        }
+        word => Err(invalid(format!("unknown condition `{}`", word))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::Condition;
+    use super::Interval;
+    use super::Rule;
+    use super::RuleSet;
+    use crate::klv_packet::tests::packet_at;
+    use crate::tag::Tag;
+
+    #[test_case("laser = laser_on for 2", Condition::LaserOn, 2_000_000; "duration")]
+    #[test_case("id = mission_id \"A B\"", Condition::MissionId("A B".into()), 0; "quoted")]
+    #[test_case(
+        "x = not has_tag 3 and (laser_on or weapon_fired)",
+        Condition::All(vec![
+            Condition::Not(Box::new(Condition::HasTag(Tag::MissionID))),
+            Condition::Any(vec![Condition::LaserOn, Condition::WeaponFired]),
+        ]),
+        0;
+        "nested"
+    )]
+    fn parse(line: &str, condition: Condition, min_duration: u64) {
+        let rule = line.parse::<Rule>().unwrap();
+        assert_eq!(rule.condition, condition);
+        assert_eq!(rule.min_duration, min_duration);
+    }
+
+    #[test_case("laser_on"; "missing name")]
+    #[test_case("x = laser_on for"; "missing duration")]
+    #[test_case("x = has_tag Nope"; "unknown tag")]
+    #[test_case("x = frame_center_within 1 2, 3 4"; "small polygon")]
+    #[test_case("x = (laser_on"; "unclosed")]
+    fn invalid(line: &str) {
+        assert!(line.parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn evaluate() {
+        let rules = "# Comment\nlaser = laser_on for 2\nanywhere = frame_center_within 0 0, 0 1, 1 1, 1 0\n"
+            .parse::<RuleSet>()
+            .unwrap();
+
+        let laser = [0x2F, 0x01, 0x01];
+        let off = [0x2F, 0x01, 0x00];
+        let packets = [
+            packet_at(1_000_000, &laser),
+            packet_at(2_000_000, &laser),
+            packet_at(3_000_000, &off),
+            packet_at(4_000_000, &laser),
+            packet_at(5_000_000, &laser),
+            packet_at(6_000_000, &laser),
+        ];
+        let matches = rules.evaluate(&packets);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(&*matches[0].rule, "laser");
+        assert_eq!(
+            matches[0].intervals,
+            [Interval { start: 4_000_000, end: 6_000_000 }]
+        );
+    }
+
+    #[test]
+    fn polygon() {
+        let condition = Condition::FrameCenterWithin(vec![
+            (0.0, 0.0),
+            (0.0, 10.0),
+            (10.0, 10.0),
+            (10.0, 0.0),
+        ]);
+        // Frame center of 4.19 degrees latitude and 8.38 degrees longitude.
+        let inside = packet_at(
+            1,
+            &[
+                0x17, 0x04, 0x05, 0xF5, 0xE1, 0x00, 0x18, 0x04, 0x05, 0xF5,
+                0xE1, 0x00,
+            ],
+        );
+        // Frame center of -4.19 degrees latitude.
+        let outside = packet_at(
+            1,
+            &[
+                0x17, 0x04, 0xFA, 0x0A, 0x1F, 0x00, 0x18, 0x04, 0x05, 0xF5,
+                0xE1, 0x00,
+            ],
+        );
+        assert!(condition.matches(&inside));
+        assert!(!condition.matches(&outside));
+    }
+}
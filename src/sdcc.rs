@@ -0,0 +1,319 @@
+//! Information from this page was gathered from MISB ST 1010.3, "Generalized
+//! Standard Deviation and Correlation Coefficient Metadata".
+//!
+//! The Standard Deviation and Cross Correlation Floating Length Pack
+//! (SDCC-FLP) is laid out as follows:
+//!
+//! | Field                    | Encoding                                    |
+//! |--------------------------|---------------------------------------------|
+//! | Matrix size `n`          | BER-OID                                     |
+//! | Element tags             | `n` BER-OID tags                            |
+//! | Parse control            | 1 byte                                      |
+//! | Sparse bit vector        | `n(n-1)/2` bits, only if flagged            |
+//! | Standard deviations      | `n` values of `Lsd` bytes                   |
+//! | Correlation coefficients | upper triangle of the matrix, row major     |
+//!
+//! The parse control bits are, from most to least significant:
+//!
+//! - `C`: A sparse bit vector follows the parse control. Correlation
+//!   coefficients whose bit is not set are not present and are zero.
+//! - `Lcc` (3 bits): Length of each correlation coefficient.
+//! - `F`: Correlation coefficients are IEEE 754 floats. Otherwise they are
+//!   IMAPB(-1.0, 1.0, `Lcc`).
+//! - `Lsd` (3 bits): Length of each standard deviation. Standard deviations
+//!   are always IEEE 754 floats.
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::ErrorKind;
+use crate::cursor::Cursor;
+use crate::imap;
+use crate::klv_packet::KlvPacket;
+use crate::local_set::Encoding;
+use crate::tag::Tag;
+
+/// Standard deviations and cross correlation coefficients for a set of tags
+/// in a UAS LS KLV packet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sdcc {
+    tags: Vec<Tag>,
+    standard_deviations: Vec<f64>,
+    /// Upper triangle of the correlation matrix, row major, excluding the
+    /// diagonal.
+    correlations: Vec<f64>,
+}
+
+impl Sdcc {
+    /// Parse the value bytes of an SDCC-FLP.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Sdcc, ErrorKind> {
+        let mut buf = Cursor::new(bytes);
+
+        let size = Self::read_ber_oid(&mut buf)?;
+        let tags = (0..size)
+            .map(|_| Self::read_ber_oid(&mut buf).map(Tag::from))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let parse_control = Self::read(&mut buf, 1)?[0] as usize;
+        let sparse = parse_control & 0x80 != 0;
+        let correlation_length = (parse_control >> 4) & 0x07;
+        let correlation_float = parse_control & 0x08 != 0;
+        let deviation_length = parse_control & 0x07;
+
+        let pair_count =
+            size.checked_mul(size.saturating_sub(1)).ok_or(Self::malformed())?
+                / 2;
+        // Each coefficient of a dense matrix takes at least one byte, so the
+        // declared size is checked against the data before allocating.
+        if !sparse && pair_count > bytes.len() - buf.position() as usize {
+            return Err(Self::malformed());
+        }
+        let present = if sparse {
+            let bit_vector = Self::read(&mut buf, pair_count.div_ceil(8))?;
+            (0..pair_count)
+                .map(|idx| bit_vector[idx / 8] & (0x80 >> (idx % 8)) != 0)
+                .collect()
+        } else {
+            vec![true; pair_count]
+        };
+
+        let standard_deviations = (0..size)
+            .map(|_| Self::float(&Self::read(&mut buf, deviation_length)?))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let correlations = present
+            .into_iter()
+            .map(|present| {
+                if !present {
+                    return Ok(0.0);
+                }
+
+                let bytes = Self::read(&mut buf, correlation_length)?;
+                if correlation_float {
+                    Self::float(&bytes)
+                } else {
+                    imap::imapb(-1.0, 1.0, &bytes).ok_or(Self::malformed())
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if buf.position() as usize != bytes.len() {
+            return Err(Self::malformed());
+        }
+
+        Ok(Sdcc { tags, standard_deviations, correlations })
+    }
+
+    /// Encode the pack as a dense matrix of IEEE 754 single precision floats,
+    /// so values are rounded to the nearest `f32`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let _ = Encoding::BerOid.write(self.tags.len(), &mut bytes);
+        for tag in &self.tags {
+            let _ = Encoding::BerOid.write(tag.id(), &mut bytes);
+        }
+        // Lcc = 4, F = 1, Lsd = 4
+        bytes.push(0x4C);
+        for value in self.standard_deviations.iter().chain(&self.correlations) {
+            bytes.extend_from_slice(&(*value as f32).to_be_bytes());
+        }
+        bytes
+    }
+
+    fn malformed() -> ErrorKind {
+        ErrorKind::MalformedValue(Tag::SdccFlp.id())
+    }
+
+    fn read_ber_oid(buf: &mut Cursor<&[u8]>) -> Result<usize, ErrorKind> {
+        KlvPacket::get_ber_oid_value(buf).map_err(|_| Self::malformed())
+    }
+
+    fn read(
+        buf: &mut Cursor<&[u8]>,
+        length: usize,
+    ) -> Result<Vec<u8>, ErrorKind> {
+        if length > buf.get_ref().len() - buf.position() as usize {
+            return Err(Self::malformed());
+        }
+        let mut bytes = vec![0; length];
+        buf.read_exact(&mut bytes).map_err(|_| Self::malformed())?;
+        Ok(bytes)
+    }
+
+    fn float(bytes: &[u8]) -> Result<f64, ErrorKind> {
+        match bytes.len() {
+            4 => Ok(f32::from_be_bytes(bytes.try_into().unwrap()) as f64),
+            8 => Ok(f64::from_be_bytes(bytes.try_into().unwrap())),
+            _ => Err(Self::malformed()),
+        }
+    }
+
+    /// Get the index of `tag` in the matrix.
+    fn index(&self, tag: Tag) -> Option<usize> {
+        self.tags.iter().position(|t| *t == tag)
+    }
+
+    /// The tags that this matrix describes, in matrix order.
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    /// Return the standard deviation for the value of `tag`.
+    pub fn standard_deviation(&self, tag: Tag) -> Option<f64> {
+        Some(self.standard_deviations[self.index(tag)?])
+    }
+
+    /// Return the correlation coefficient between the values of the two tags.
+    pub fn correlation(&self, a: Tag, b: Tag) -> Option<f64> {
+        let (a, b) = (self.index(a)?, self.index(b)?);
+        Some(self.correlation_at(a, b))
+    }
+
+    fn correlation_at(&self, a: usize, b: usize) -> f64 {
+        if a == b {
+            return 1.0;
+        }
+
+        // Coefficients are stored for the upper triangle only.
+        let (row, col) = if a < b { (a, b) } else { (b, a) };
+        let size = self.tags.len();
+        let row_start = row * size - row * (row + 1) / 2;
+        self.correlations[row_start + col - row - 1]
+    }
+
+    /// Return the covariance between the values of the two tags.
+    pub fn covariance(&self, a: Tag, b: Tag) -> Option<f64> {
+        let (a, b) = (self.index(a)?, self.index(b)?);
+        Some(self.covariance_at(a, b))
+    }
+
+    fn covariance_at(&self, a: usize, b: usize) -> f64 {
+        self.correlation_at(a, b)
+            * self.standard_deviations[a]
+            * self.standard_deviations[b]
+    }
+
+    /// Return the full covariance matrix, in the order of [`Sdcc::tags`].
+    pub fn covariance_matrix(&self) -> Vec<Vec<f64>> {
+        let size = self.tags.len();
+        (0..size)
+            .map(|a| (0..size).map(|b| self.covariance_at(a, b)).collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+
+    use super::Sdcc;
+    use crate::tag::Tag;
+
+    /// Generate dense packs whose values are exact in single precision.
+    pub(crate) fn sdcc() -> impl Strategy<Value = Sdcc> {
+        let tags: Vec<Tag> = Tag::iter()
+            .filter(|tag| *tag != Tag::Unknown && *tag != Tag::Deprecated)
+            .collect();
+        (1usize..6)
+            .prop_flat_map(move |size| {
+                (
+                    vec(select(tags.clone()), size),
+                    vec(-1.0e6f32..1.0e6, size),
+                    vec(-1.0f32..=1.0, size * (size - 1) / 2),
+                )
+            })
+            .prop_map(|(tags, deviations, correlations)| Sdcc {
+                tags,
+                standard_deviations: deviations
+                    .into_iter()
+                    .map(f64::from)
+                    .collect(),
+                correlations: correlations.into_iter().map(f64::from).collect(),
+            })
+    }
+
+    fn sdcc_bytes(parse_control: u8, tail: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0x03, 13, 14, 15, parse_control];
+        bytes.extend_from_slice(tail);
+        bytes
+    }
+
+    #[test]
+    fn dense_float() {
+        let mut tail = Vec::new();
+        for sd in [1.0f32, 2.0, 4.0] {
+            tail.extend_from_slice(&sd.to_be_bytes());
+        }
+        for cc in [0.5f32, 0.25, -0.5] {
+            tail.extend_from_slice(&cc.to_be_bytes());
+        }
+        // Lcc = 4, F = 1, Lsd = 4
+        let sdcc = Sdcc::from_bytes(&sdcc_bytes(0x4C, &tail)).unwrap();
+
+        assert_eq!(
+            sdcc.tags(),
+            &[
+                Tag::SensorLatitude,
+                Tag::SensorLongitude,
+                Tag::SensorTrueAltitude
+            ]
+        );
+        assert_eq!(sdcc.standard_deviation(Tag::SensorLongitude), Some(2.0));
+        assert_eq!(
+            sdcc.correlation(Tag::SensorTrueAltitude, Tag::SensorLongitude),
+            Some(-0.5)
+        );
+        assert_eq!(
+            sdcc.covariance(Tag::SensorLatitude, Tag::SensorTrueAltitude),
+            Some(1.0)
+        );
+        assert_eq!(sdcc.covariance_matrix()[1][1], 4.0);
+        assert_eq!(sdcc.standard_deviation(Tag::SlantRange), None);
+    }
+
+    #[test]
+    fn sparse_imapb() {
+        let mut tail = vec![0b0100_0000];
+        for sd in [1.0f32, 2.0, 4.0] {
+            tail.extend_from_slice(&sd.to_be_bytes());
+        }
+        tail.extend_from_slice(&crate::imap::to_imapb(-1.0, 1.0, 2, 0.5));
+        // C = 1, Lcc = 2, F = 0, Lsd = 4
+        let sdcc = Sdcc::from_bytes(&sdcc_bytes(0xA4, &tail)).unwrap();
+
+        assert_eq!(
+            sdcc.correlation(Tag::SensorLatitude, Tag::SensorLongitude),
+            Some(0.0)
+        );
+        assert_eq!(
+            sdcc.correlation(Tag::SensorLatitude, Tag::SensorTrueAltitude),
+            Some(0.5)
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        assert!(Sdcc::from_bytes(&sdcc_bytes(0x4C, &[0x00, 0x01])).is_err());
+    }
+
+    #[test]
+    fn oversized() {
+        // A huge matrix size with only two tags.
+        assert!(
+            Sdcc::from_bytes(&[0x8F, 0xFF, 0xFF, 0xFF, 0x7F, 1, 2]).is_err()
+        );
+        // A dense matrix of 120 tags with no values.
+        let mut bytes = vec![0x78];
+        bytes.extend(1..=120);
+        bytes.push(0x4C);
+        assert!(Sdcc::from_bytes(&bytes).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip(sdcc in sdcc()) {
+            prop_assert_eq!(Sdcc::from_bytes(&sdcc.to_bytes()).unwrap(), sdcc);
+        }
+    }
+}
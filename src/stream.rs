@@ -0,0 +1,142 @@
+//! Asynchronous ingestion of KLV packets from live MPEG-2 Transport Streams.
+//!
+//! [`KlvStream`] reads transport stream data from any [`AsyncRead`] or from a
+//! [`UdpSocket`] and demultiplexes it with [`TsDemuxer`], yielding each KLV
+//! packet as soon as it is complete. Data is only read while the stream is
+//! being polled, so a slow consumer applies backpressure to the source.
+use std::io;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::task::ready;
+
+use futures_core::Stream;
+use tokio::io::AsyncRead;
+use tokio::io::ReadBuf;
+use tokio::net::UdpSocket;
+
+use crate::ErrorKind;
+use crate::klv_packet::KlvPacket;
+use crate::mpegts::TsDemuxer;
+
+/// Large enough to hold any UDP datagram.
+const BUFFER_SIZE: usize = 65_536;
+
+/// An [`AsyncRead`] over the datagrams received by a [`UdpSocket`].
+#[derive(Debug)]
+pub struct UdpReader(UdpSocket);
+
+impl AsyncRead for UdpReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        // Empty datagrams would otherwise be mistaken for the end of the
+        // stream.
+        let filled = buf.filled().len();
+        while buf.filled().len() == filled {
+            ready!(self.0.poll_recv(cx, buf))?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A [`Stream`] of the KLV packets in a transport stream.
+pub struct KlvStream<R> {
+    reader: R,
+    demuxer: TsDemuxer,
+    buf: Box<[u8]>,
+    finished: bool,
+}
+
+impl<R: AsyncRead + Unpin> KlvStream<R> {
+    /// Read the transport stream from `reader`.
+    pub fn new(reader: R) -> KlvStream<R> {
+        KlvStream {
+            reader,
+            demuxer: TsDemuxer::new(),
+            buf: vec![0; BUFFER_SIZE].into_boxed_slice(),
+            finished: false,
+        }
+    }
+
+    /// Treat `pid` as a KLV metadata stream even if no Program Map Table
+    /// describes it.
+    pub fn add_pid(&mut self, pid: u16) {
+        self.demuxer.add_pid(pid);
+    }
+}
+
+impl KlvStream<UdpReader> {
+    /// Read the transport stream from the datagrams received by `socket`.
+    pub fn from_udp(socket: UdpSocket) -> KlvStream<UdpReader> {
+        KlvStream::new(UdpReader(socket))
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for KlvStream<R> {
+    type Item = Result<KlvPacket, ErrorKind>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(packet) = this.demuxer.pop() {
+                return Poll::Ready(Some(packet.map(|timed| timed.packet)));
+            }
+            if this.finished {
+                return Poll::Ready(None);
+            }
+
+            let mut buf = ReadBuf::new(&mut this.buf);
+            match ready!(Pin::new(&mut this.reader).poll_read(cx, &mut buf)) {
+                Ok(()) if buf.filled().is_empty() => {
+                    this.finished = true;
+                    this.demuxer.finish();
+                }
+                Ok(()) => this.demuxer.push(buf.filled()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    this.finished = true;
+                    return Poll::Ready(Some(Err(ErrorKind::Io(e))));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::poll_fn;
+    use std::pin::Pin;
+
+    use futures_core::Stream;
+
+    use super::KlvStream;
+    use crate::mpegts::tests::klv_packet;
+    use crate::mpegts::tests::pat_and_pmt;
+    use crate::mpegts::tests::pes;
+    use crate::mpegts::tests::ts_packet;
+
+    #[tokio::test]
+    async fn read() {
+        let mut stream = vec![0x47, 0x00];
+        stream.extend(pat_and_pmt(0x0102));
+        for timestamp in 1..=3 {
+            let pes = pes(0xBD, timestamp * 3003, &klv_packet(timestamp));
+            stream.extend(ts_packet(0x0102, true, &pes));
+        }
+
+        let mut klv = KlvStream::new(stream.as_slice());
+        let mut timestamps = Vec::new();
+        while let Some(packet) =
+            poll_fn(|cx| Pin::new(&mut klv).poll_next(cx)).await
+        {
+            timestamps.push(packet.unwrap().precision_time_stamp().unwrap());
+        }
+        assert_eq!(timestamps, vec![1, 2, 3]);
+    }
+}
@@ -8,7 +8,35 @@
 //! duplication.
 //! https://stackoverflow.com/questions/36928569/how-can-i-create-enums-with-constant-values-in-rust
 
+use alloc::vec::Vec;
+
+use crate::ErrorKind;
+use crate::flags::ActivePayloads;
+use crate::flags::GenericFlags;
+use crate::flags::IcingDetected;
+use crate::flags::OperationalMode;
+use crate::flags::PlatformStatus;
+use crate::flags::PositionMethodSource;
+use crate::flags::SensorControlMode;
+use crate::flags::SensorFieldOfViewName;
+use crate::klv_packet::KlvPacket;
 use crate::klv_value::KlvValueType;
+use crate::miis::MiisCoreId;
+use crate::sdcc::Sdcc;
+use crate::units::Celsius;
+use crate::units::Degrees;
+use crate::units::DegreesPerSecond;
+use crate::units::Kilograms;
+use crate::units::Kilometers;
+use crate::units::Megahertz;
+use crate::units::Meters;
+use crate::units::MetersPerSecond;
+use crate::units::Millibars;
+use crate::units::Percent;
+use crate::units::Pixels;
+use crate::weapon::WeaponFired;
+use crate::weapon::WeaponLoad;
+use crate::weapon::WeaponStore;
 
 /// Reference summary section of the [`KlvValue`] page for more in-depth reasoning but this macro
 /// is shamelessly stolen from an absolute genius's
@@ -18,7 +46,10 @@ macro_rules! def_tags {
     (
         $(#[$attr:meta])*
         $vis:vis $name:ident => $ret_typ:ty, $ret_id:ty {
-            $($variant:ident => $typ:expr, $id:expr);+
+            $(
+                $variant:ident => $typ:expr, $id:expr
+                $(, $accessor:ident: $accessor_typ:ty)?
+            );+
             $(;)?
         }
     ) => {
@@ -36,6 +67,11 @@ macro_rules! def_tags {
             pub const VARIANTS: &'static [Self] = &[$(Self::$variant),+];
             pub const COUNT: usize = Self::VARIANTS.len();
 
+            /// Every variant, in order of ID.
+            pub fn iter() -> impl Iterator<Item = Self> {
+                Self::VARIANTS.iter().copied()
+            }
+
             pub const fn tag_type(self) -> $ret_typ {
                 self.0
             }
@@ -47,23 +83,31 @@ macro_rules! def_tags {
             pub const fn string(self) -> &'static str {
                 self.2
             }
+
+            /// Find the variant with the given name, ignoring case.
+            pub fn from_name(name: &str) -> Option<Self> {
+                Self::VARIANTS
+                    .iter()
+                    .copied()
+                    .find(|v| v.string().eq_ignore_ascii_case(name))
+            }
         }
 
-        impl Into<&'static str> for $name {
-            fn into(self) -> &'static str {
-                self.string()
+        impl From<$name> for &'static str {
+            fn from(value: $name) -> &'static str {
+                value.string()
             }
         }
 
-        impl Into<$ret_typ> for $name {
-            fn into(self) -> $ret_typ {
-                self.tag_type()
+        impl From<$name> for $ret_typ {
+            fn from(value: $name) -> $ret_typ {
+                value.tag_type()
             }
         }
 
-        impl Into<$ret_id> for $name {
-            fn into(self) -> $ret_id {
-                self.id()
+        impl From<$name> for $ret_id {
+            fn from(value: $name) -> $ret_id {
+                value.id()
             }
         }
 
@@ -75,155 +119,459 @@ macro_rules! def_tags {
                 }
             }
         }
+
+        // Every variant has a different ID, so hashing and ordering by ID
+        // agrees with the derived equality.
+        impl core::hash::Hash for $name {
+            fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+                self.id().hash(state);
+            }
+        }
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+                self.id().cmp(&other.id())
+            }
+        }
+
+        impl core::fmt::Display for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str(self.string())
+            }
+        }
+
+        // Accessors for the value of each variant that has one.
+        impl KlvPacket {
+            $($(
+                #[doc = concat!(
+                    "Return the value of [`", stringify!($name), "::",
+                    stringify!($variant), "`]\n\nReturns `None` if the packet ",
+                    "doesn't have the tag or its value is the wrong type or ",
+                    "invalid."
+                )]
+                pub fn $accessor(&self) -> Option<$accessor_typ> {
+                    self.typed($name::$variant)
+                }
+            )?)+
+        }
+
+        /// Parse a variant from its name, ignoring case, or from its ID.
+        impl core::str::FromStr for $name {
+            type Err = ErrorKind;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let variant = match s.parse::<$ret_id>() {
+                    Ok(id) => Self::iter().find(|v| v.id() == id),
+                    Err(_) => Self::from_name(s),
+                };
+                variant.ok_or_else(|| ErrorKind::InvalidString(s.into()))
+            }
+        }
     };
 }
 
 def_tags! {
-    #[derive(Clone, Copy, Debug, PartialEq)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     pub Tag => KlvValueType, usize {
         Unknown                                     => KlvValueType::Unknown,       0;
-        Checksum                                    => KlvValueType::Uint16,        1;
-        PrecisionTimeStamp                          => KlvValueType::Uint64,        2;
-        MissionID                                   => KlvValueType::Utf8,          3;
-        PlatformTailNumber                          => KlvValueType::Utf8,          4;
-        PlatformHeadingAngle                        => KlvValueType::Uint16,        5;
-        PlatformPitchAngle                          => KlvValueType::Int16,         6;
-        PlatformRollAngle                           => KlvValueType::Int16,         7;
-        PlatformTrueAirspeed                        => KlvValueType::Uint8,         8;
-        PlatformIndicatedAirspeed                   => KlvValueType::Uint8,         9;
-        PlatformDesignation                         => KlvValueType::Utf8,          10;
-        ImageSourceSensor                           => KlvValueType::Utf8,          11;
-        ImageCoordinateSystem                       => KlvValueType::Utf8,          12;
-        SensorLatitude                              => KlvValueType::Int32,         13;
-        SensorLongitude                             => KlvValueType::Int32,         14;
-        SensorTrueAltitude                          => KlvValueType::Uint16,        15;
-        SensorHorizontalFieldOfView                 => KlvValueType::Uint16,        16;
-        SensorVerticalFieldOfView                   => KlvValueType::Uint16,        17;
-        SensorRelativeAzimuthAngle                  => KlvValueType::Uint32,        18;
-        SensorRelativeElevationAngle                => KlvValueType::Int32,         19;
-        SensorRelativeRollAngle                     => KlvValueType::Uint32,        20;
-        SlantRange                                  => KlvValueType::Uint32,        21;
-        TargetWidth                                 => KlvValueType::Uint16,        22;
-        FrameCenterLatitude                         => KlvValueType::Int32,         23;
-        FrameCenterLongitude                        => KlvValueType::Int32,         24;
-        FrameCenterElevation                        => KlvValueType::Uint16,        25;
-        OffsetCornerLatitudePoint1                  => KlvValueType::Int16,         26;
-        OffsetCornerLongitudePoint1                 => KlvValueType::Int16,         27;
-        OffsetCornerLatitudePoint2                  => KlvValueType::Int16,         28;
-        OffsetCornerLongitudePoint2                 => KlvValueType::Int16,         29;
-        OffsetCornerLatitudePoint3                  => KlvValueType::Int16,         30;
-        OffsetCornerLongitudePoint3                 => KlvValueType::Int16,         31;
-        OffsetCornerLatitudePoint4                  => KlvValueType::Int16,         32;
-        OffsetCornerLongitudePoint4                 => KlvValueType::Int16,         33;
-        IcingDetected                               => KlvValueType::Uint8,         34;
-        WindDirection                               => KlvValueType::Uint16,        35;
-        WindSpeed                                   => KlvValueType::Uint8,         36;
-        StaticPressure                              => KlvValueType::Uint16,        37;
-        DensityAltitude                             => KlvValueType::Uint16,        38;
-        OutsideAirTemperature                       => KlvValueType::Int8,          39;
-        TargetLocationLatitude                      => KlvValueType::Int32,         40;
-        TargetLocationLongitude                     => KlvValueType::Int32,         41;
-        TargetLocationElevation                     => KlvValueType::Uint16,        42;
-        TargetTrackGateWidth                        => KlvValueType::Uint8,         43;
-        TargetTrackGateHeight                       => KlvValueType::Uint8,         44;
-        TargetErrorEstimateCE90                     => KlvValueType::Uint16,        45;
-        TargetErrorEstimateLe90                     => KlvValueType::Uint16,        46;
-        GenericFlagData                             => KlvValueType::Uint8,         47;
-        SecurityLocalSet                            => KlvValueType::Set,           48;
-        DifferentialPressure                        => KlvValueType::Uint16,        49;
-        PlatformAngleOfAttack                       => KlvValueType::Int16,         50;
-        PlatformVerticalSpeed                       => KlvValueType::Int16,         51;
-        PlatformSideslipAngle                       => KlvValueType::Int16,         52;
-        AirfieldBarometricPressure                  => KlvValueType::Uint16,        53;
-        AirfieldElevation                           => KlvValueType::Uint16,        54;
-        RelativeHumidity                            => KlvValueType::Uint8,         55;
-        PlatformGroundSpeed                         => KlvValueType::Uint8,         56;
-        GroundRange                                 => KlvValueType::Uint32,        57;
-        PlatformFuelRemaining                       => KlvValueType::Uint16,        58;
-        PlatformCallSign                            => KlvValueType::Utf8,          59;
-        WeaponLoad                                  => KlvValueType::Uint16,        60;
-        WeaponFired                                 => KlvValueType::Uint8,         61;
-        LaserPrfCode                                => KlvValueType::Uint16,        62;
-        SensorFieldOfViewName                       => KlvValueType::Uint8,         63;
-        PlatformMagneticHeading                     => KlvValueType::Uint16,        64;
-        UasDatalinkLsVersionNumber                  => KlvValueType::Uint8,         65;
+        Checksum                                    => KlvValueType::Uint16,       1,   checksum: u16;
+        PrecisionTimeStamp                          => KlvValueType::Uint64,       2,   precision_time_stamp: u64;
+        MissionID                                   => KlvValueType::Utf8,         3,   mission_id: &str;
+        PlatformTailNumber                          => KlvValueType::Utf8,         4,   platform_tail_number: &str;
+        PlatformHeadingAngle                        => KlvValueType::Uint16,       5,   platform_heading_angle: Degrees;
+        PlatformPitchAngle                          => KlvValueType::Int16,        6,   platform_pitch_angle: Degrees;
+        PlatformRollAngle                           => KlvValueType::Int16,        7,   platform_roll_angle: Degrees;
+        PlatformTrueAirspeed                        => KlvValueType::Uint8,        8,   platform_true_airspeed: MetersPerSecond;
+        PlatformIndicatedAirspeed                   => KlvValueType::Uint8,        9,   platform_indicated_airspeed: MetersPerSecond;
+        PlatformDesignation                         => KlvValueType::Utf8,         10,  platform_designation: &str;
+        ImageSourceSensor                           => KlvValueType::Utf8,         11,  image_source_sensor: &str;
+        ImageCoordinateSystem                       => KlvValueType::Utf8,         12,  image_coordinate_system: &str;
+        SensorLatitude                              => KlvValueType::Int32,        13,  sensor_latitude: Degrees;
+        SensorLongitude                             => KlvValueType::Int32,        14,  sensor_longitude: Degrees;
+        SensorTrueAltitude                          => KlvValueType::Uint16,       15,  sensor_true_altitude: Meters;
+        SensorHorizontalFieldOfView                 => KlvValueType::Uint16,       16,  sensor_horizontal_field_of_view: Degrees;
+        SensorVerticalFieldOfView                   => KlvValueType::Uint16,       17,  sensor_vertical_field_of_view: Degrees;
+        SensorRelativeAzimuthAngle                  => KlvValueType::Uint32,       18,  sensor_relative_azimuth_angle: Degrees;
+        SensorRelativeElevationAngle                => KlvValueType::Int32,        19,  sensor_relative_elevation_angle: Degrees;
+        SensorRelativeRollAngle                     => KlvValueType::Uint32,       20,  sensor_relative_roll_angle: Degrees;
+        SlantRange                                  => KlvValueType::Uint32,       21,  slant_range: Meters;
+        TargetWidth                                 => KlvValueType::Uint16,       22,  target_width: Meters;
+        FrameCenterLatitude                         => KlvValueType::Int32,        23,  frame_center_latitude: Degrees;
+        FrameCenterLongitude                        => KlvValueType::Int32,        24,  frame_center_longitude: Degrees;
+        FrameCenterElevation                        => KlvValueType::Uint16,       25,  frame_center_elevation: Meters;
+        OffsetCornerLatitudePoint1                  => KlvValueType::Int16,        26,  offset_corner_latitude_point_1: Degrees;
+        OffsetCornerLongitudePoint1                 => KlvValueType::Int16,        27,  offset_corner_longitude_point_1: Degrees;
+        OffsetCornerLatitudePoint2                  => KlvValueType::Int16,        28,  offset_corner_latitude_point_2: Degrees;
+        OffsetCornerLongitudePoint2                 => KlvValueType::Int16,        29,  offset_corner_longitude_point_2: Degrees;
+        OffsetCornerLatitudePoint3                  => KlvValueType::Int16,        30,  offset_corner_latitude_point_3: Degrees;
+        OffsetCornerLongitudePoint3                 => KlvValueType::Int16,        31,  offset_corner_longitude_point_3: Degrees;
+        OffsetCornerLatitudePoint4                  => KlvValueType::Int16,        32,  offset_corner_latitude_point_4: Degrees;
+        OffsetCornerLongitudePoint4                 => KlvValueType::Int16,        33,  offset_corner_longitude_point_4: Degrees;
+        IcingDetected                               => KlvValueType::Uint8,        34,  icing_detected: IcingDetected;
+        WindDirection                               => KlvValueType::Uint16,       35,  wind_direction: Degrees;
+        WindSpeed                                   => KlvValueType::Uint8,        36,  wind_speed: MetersPerSecond;
+        StaticPressure                              => KlvValueType::Uint16,       37,  static_pressure: Millibars;
+        DensityAltitude                             => KlvValueType::Uint16,       38,  density_altitude: Meters;
+        OutsideAirTemperature                       => KlvValueType::Int8,         39,  outside_air_temperature: Celsius;
+        TargetLocationLatitude                      => KlvValueType::Int32,        40,  target_location_latitude: Degrees;
+        TargetLocationLongitude                     => KlvValueType::Int32,        41,  target_location_longitude: Degrees;
+        TargetLocationElevation                     => KlvValueType::Uint16,       42,  target_location_elevation: Meters;
+        TargetTrackGateWidth                        => KlvValueType::Uint8,        43,  target_track_gate_width: Pixels;
+        TargetTrackGateHeight                       => KlvValueType::Uint8,        44,  target_track_gate_height: Pixels;
+        TargetErrorEstimateCE90                     => KlvValueType::Uint16,       45,  target_error_estimate_ce90: Meters;
+        TargetErrorEstimateLe90                     => KlvValueType::Uint16,       46,  target_error_estimate_le90: Meters;
+        GenericFlagData                             => KlvValueType::Uint8,        47,  generic_flags: GenericFlags;
+        SecurityLocalSet                            => KlvValueType::Set,          48;
+        DifferentialPressure                        => KlvValueType::Uint16,       49,  differential_pressure: Millibars;
+        PlatformAngleOfAttack                       => KlvValueType::Int16,        50,  platform_angle_of_attack: Degrees;
+        PlatformVerticalSpeed                       => KlvValueType::Int16,        51,  platform_vertical_speed: MetersPerSecond;
+        PlatformSideslipAngle                       => KlvValueType::Int16,        52,  platform_sideslip_angle: Degrees;
+        AirfieldBarometricPressure                  => KlvValueType::Uint16,       53,  airfield_barometric_pressure: Millibars;
+        AirfieldElevation                           => KlvValueType::Uint16,       54,  airfield_elevation: Meters;
+        RelativeHumidity                            => KlvValueType::Uint8,        55,  relative_humidity: Percent;
+        PlatformGroundSpeed                         => KlvValueType::Uint8,        56,  platform_ground_speed: MetersPerSecond;
+        GroundRange                                 => KlvValueType::Uint32,       57,  ground_range: Meters;
+        PlatformFuelRemaining                       => KlvValueType::Uint16,       58,  platform_fuel_remaining: Kilograms;
+        PlatformCallSign                            => KlvValueType::Utf8,         59,  platform_call_sign: &str;
+        WeaponLoad                                  => KlvValueType::Uint16,       60,  weapon_load: WeaponLoad;
+        WeaponFired                                 => KlvValueType::Uint8,        61,  weapon_fired: WeaponFired;
+        LaserPrfCode                                => KlvValueType::Uint16,       62,  laser_prf_code: u16;
+        SensorFieldOfViewName                       => KlvValueType::Uint8,        63,  sensor_field_of_view_name: SensorFieldOfViewName;
+        PlatformMagneticHeading                     => KlvValueType::Uint16,       64,  platform_magnetic_heading: Degrees;
+        UasDatalinkLsVersionNumber                  => KlvValueType::Uint8,        65,  uas_datalink_ls_version_number: u8;
         Deprecated                                  => KlvValueType::Deprecated,    66;
-        AlternatePlatformLatitude                   => KlvValueType::Int32,         67;
-        AlternatePlatformLongitude                  => KlvValueType::Int32,         68;
-        AlternatePlatformAltitude                   => KlvValueType::Uint16,        69;
-        AlternatePlatformName                       => KlvValueType::Utf8,          70;
-        AlternatePlatformHeading                    => KlvValueType::Uint16,        71;
-        EventStartTime                              => KlvValueType::Uint64,        72;
-        RvtLocalSet                                 => KlvValueType::Set,           73;
-        VmtiLocalSet                                => KlvValueType::Set,           74;
-        SensorEllipsoidHeight                       => KlvValueType::Uint16,        75;
-        AlternatePlatformEllipsoidHeight            => KlvValueType::Uint16,        76;
-        OperationalMode                             => KlvValueType::Uint8,         77;
-        FrameCenterHeightAboveEllipsoid             => KlvValueType::Uint16,        78;
-        SensorNorthVelocity                         => KlvValueType::Int16,         79;
-        SensorEastVelocity                          => KlvValueType::Int16,         80;
-        ImageHorizonPixelPack                       => KlvValueType::DLP,           81;
-        CornerLatitudePoint1Full                    => KlvValueType::Int32,         82;
-        CornerLongitudePoint1Full                   => KlvValueType::Int32,         83;
-        CornerLatitudePoint2Full                    => KlvValueType::Int32,         84;
-        CornerLongitudePoint2Full                   => KlvValueType::Int32,         85;
-        CornerLatitudePoint3Full                    => KlvValueType::Int32,         86;
-        CornerLongitudePoint3Full                   => KlvValueType::Int32,         87;
-        CornerLatitudePoint4Full                    => KlvValueType::Int32,         88;
-        CornerLongitudePoint4Full                   => KlvValueType::Int32,         89;
-        PlatformPitchAngleFull                      => KlvValueType::Int32,         90;
-        PlatformRollAngleFull                       => KlvValueType::Int32,         91;
-        PlatformAngleOfAttackFull                   => KlvValueType::Int32,         92;
-        PlatformSideslipAngleFull                   => KlvValueType::Int32,         93;
-        MiisCoreIdentifier                          => KlvValueType::Byte,          94;
-        SarMotionImageryLocalSet                    => KlvValueType::Set,           95;
-        TargetWidthExtended                         => KlvValueType::IMAPB,         96;
-        RangeImageLocalSet                          => KlvValueType::Set,           97;
-        GeoRegistrationLocalSet                     => KlvValueType::Set,           98;
-        CompositeImagingLocalSet                    => KlvValueType::Set,           99;
-        SegmentLocalSet                             => KlvValueType::Set,           100;
-        AmendLocalSet                               => KlvValueType::Set,           101;
-        SdccFlp                                     => KlvValueType::FLP,           102;
-        DensityAltitudeExtended                     => KlvValueType::IMAPB,         103;
-        SensorEllipsoidHeightExtended               => KlvValueType::IMAPB,         104;
-        AlternatePlatformEllipsoidHeightExtended    => KlvValueType::IMAPB,         105;
-        StreamDesignator                            => KlvValueType::Utf8,          106;
-        OperationalBase                             => KlvValueType::Utf8,          107;
-        BroadcastSource                             => KlvValueType::Utf8,          108;
-        RangeToRecoveryLocation                     => KlvValueType::IMAPB,         109;
-        TimeAirborne                                => KlvValueType::Uint,          110;
-        PropulsionUnitSpeed                         => KlvValueType::Uint,          111;
-        PlatformCourseAngle                         => KlvValueType::IMAPB,         112;
-        AltitudeAgl                                 => KlvValueType::IMAPB,         113;
-        RadarAltimeter                              => KlvValueType::IMAPB,         114;
-        ControlCommand                              => KlvValueType::DLP,           115;
-        ControlCommandVerificationList              => KlvValueType::DLP,           116;
-        SensorAzimuthRate                           => KlvValueType::IMAPB,         117;
-        SensorElevationRate                         => KlvValueType::IMAPB,         118;
-        SensorRollRate                              => KlvValueType::IMAPB,         119;
-        OnboardMiStoragePercentFull                 => KlvValueType::IMAPB,         120;
-        ActiveWaypointList                          => KlvValueType::DLP,           121;
-        CountryCodes                                => KlvValueType::VLP,           122;
-        NumberOfNavsatsInView                       => KlvValueType::Uint,          123;
-        PositionMethodSource                        => KlvValueType::Uint,          124;
-        PlatformStatus                              => KlvValueType::Uint,          125;
-        SensorControlMode                           => KlvValueType::Uint,          126;
-        SensorFrameRatePack                         => KlvValueType::DLP,           127;
-        WavelengthsList                             => KlvValueType::VLP,           128;
-        TargetId                                    => KlvValueType::Utf8,          129;
-        AirbaseLocations                            => KlvValueType::VLP,           130;
-        TakeoffTime                                 => KlvValueType::Uint,          131;
-        TransmissionFrequency                       => KlvValueType::IMAPB,         132;
-        OnboardMiStorageCapacity                    => KlvValueType::Uint,          133;
-        ZoomPercentage                              => KlvValueType::IMAPB,         134;
-        CommunicationsMethod                        => KlvValueType::Utf8,          135;
-        LeapSeconds                                 => KlvValueType::Int,           136;
-        CorrectionOffset                            => KlvValueType::Int,           137;
-        PayloadList                                 => KlvValueType::VLP,           138;
-        ActivePayloads                              => KlvValueType::Byte,          139;
-        WeaponStores                                => KlvValueType::VLP,           140;
-        WaypointList                                => KlvValueType::VLP,           141;
-        ViewDomain                                  => KlvValueType::VLP,           142;
-        MetadataSubstreamIdPack                     => KlvValueType::Byte,          143;
+        AlternatePlatformLatitude                   => KlvValueType::Int32,        67,  alternate_platform_latitude: Degrees;
+        AlternatePlatformLongitude                  => KlvValueType::Int32,        68,  alternate_platform_longitude: Degrees;
+        AlternatePlatformAltitude                   => KlvValueType::Uint16,       69,  alternate_platform_altitude: Meters;
+        AlternatePlatformName                       => KlvValueType::Utf8,         70,  alternate_platform_name: &str;
+        AlternatePlatformHeading                    => KlvValueType::Uint16,       71,  alternate_platform_heading: Degrees;
+        EventStartTime                              => KlvValueType::Uint64,       72,  event_start_time: u64;
+        RvtLocalSet                                 => KlvValueType::Set,          73;
+        VmtiLocalSet                                => KlvValueType::Set,          74;
+        SensorEllipsoidHeight                       => KlvValueType::Uint16,       75,  sensor_ellipsoid_height: Meters;
+        AlternatePlatformEllipsoidHeight            => KlvValueType::Uint16,       76,  alternate_platform_ellipsoid_height: Meters;
+        OperationalMode                             => KlvValueType::Uint8,        77,  operational_mode: OperationalMode;
+        FrameCenterHeightAboveEllipsoid             => KlvValueType::Uint16,       78,  frame_center_height_above_ellipsoid: Meters;
+        SensorNorthVelocity                         => KlvValueType::Int16,        79,  sensor_north_velocity: MetersPerSecond;
+        SensorEastVelocity                          => KlvValueType::Int16,        80,  sensor_east_velocity: MetersPerSecond;
+        ImageHorizonPixelPack                       => KlvValueType::DLP,          81,  image_horizon_pixel_pack: &[u8];
+        CornerLatitudePoint1Full                    => KlvValueType::Int32,        82,  corner_latitude_point_1_full: Degrees;
+        CornerLongitudePoint1Full                   => KlvValueType::Int32,        83,  corner_longitude_point_1_full: Degrees;
+        CornerLatitudePoint2Full                    => KlvValueType::Int32,        84,  corner_latitude_point_2_full: Degrees;
+        CornerLongitudePoint2Full                   => KlvValueType::Int32,        85,  corner_longitude_point_2_full: Degrees;
+        CornerLatitudePoint3Full                    => KlvValueType::Int32,        86,  corner_latitude_point_3_full: Degrees;
+        CornerLongitudePoint3Full                   => KlvValueType::Int32,        87,  corner_longitude_point_3_full: Degrees;
+        CornerLatitudePoint4Full                    => KlvValueType::Int32,        88,  corner_latitude_point_4_full: Degrees;
+        CornerLongitudePoint4Full                   => KlvValueType::Int32,        89,  corner_longitude_point_4_full: Degrees;
+        PlatformPitchAngleFull                      => KlvValueType::Int32,        90,  platform_pitch_angle_full: Degrees;
+        PlatformRollAngleFull                       => KlvValueType::Int32,        91,  platform_roll_angle_full: Degrees;
+        PlatformAngleOfAttackFull                   => KlvValueType::Int32,        92,  platform_angle_of_attack_full: Degrees;
+        PlatformSideslipAngleFull                   => KlvValueType::Int32,        93,  platform_sideslip_angle_full: Degrees;
+        MiisCoreIdentifier                          => KlvValueType::Byte,         94,  miis_core_identifier: MiisCoreId;
+        SarMotionImageryLocalSet                    => KlvValueType::Set,          95;
+        TargetWidthExtended                         => KlvValueType::IMAPB,        96,  target_width_extended: Meters;
+        RangeImageLocalSet                          => KlvValueType::Set,          97;
+        GeoRegistrationLocalSet                     => KlvValueType::Set,          98;
+        CompositeImagingLocalSet                    => KlvValueType::Set,          99;
+        SegmentLocalSet                             => KlvValueType::Set,          100;
+        AmendLocalSet                               => KlvValueType::Set,          101;
+        SdccFlp                                     => KlvValueType::FLP,          102, sdcc: &Sdcc;
+        DensityAltitudeExtended                     => KlvValueType::IMAPB,        103, density_altitude_extended: Meters;
+        SensorEllipsoidHeightExtended               => KlvValueType::IMAPB,        104, sensor_ellipsoid_height_extended: Meters;
+        AlternatePlatformEllipsoidHeightExtended    => KlvValueType::IMAPB,        105, alternate_platform_ellipsoid_height_extended: Meters;
+        StreamDesignator                            => KlvValueType::Utf8,         106, stream_designator: &str;
+        OperationalBase                             => KlvValueType::Utf8,         107, operational_base: &str;
+        BroadcastSource                             => KlvValueType::Utf8,         108, broadcast_source: &str;
+        RangeToRecoveryLocation                     => KlvValueType::IMAPB,        109, range_to_recovery_location: Kilometers;
+        TimeAirborne                                => KlvValueType::Uint,         110, time_airborne: u64;
+        PropulsionUnitSpeed                         => KlvValueType::Uint,         111, propulsion_unit_speed: u64;
+        PlatformCourseAngle                         => KlvValueType::IMAPB,        112, platform_course_angle: Degrees;
+        AltitudeAgl                                 => KlvValueType::IMAPB,        113, altitude_agl: Meters;
+        RadarAltimeter                              => KlvValueType::IMAPB,        114, radar_altimeter: Meters;
+        ControlCommand                              => KlvValueType::DLP,          115, control_command: &[u8];
+        ControlCommandVerificationList              => KlvValueType::DLP,          116, control_command_verification_list: &[u8];
+        SensorAzimuthRate                           => KlvValueType::IMAPB,        117, sensor_azimuth_rate: DegreesPerSecond;
+        SensorElevationRate                         => KlvValueType::IMAPB,        118, sensor_elevation_rate: DegreesPerSecond;
+        SensorRollRate                              => KlvValueType::IMAPB,        119, sensor_roll_rate: DegreesPerSecond;
+        OnboardMiStoragePercentFull                 => KlvValueType::IMAPB,        120, onboard_mi_storage_percent_full: Percent;
+        ActiveWaypointList                          => KlvValueType::DLP,          121, active_waypoint_list: &[u8];
+        CountryCodes                                => KlvValueType::VLP,          122, country_codes: &[u8];
+        NumberOfNavsatsInView                       => KlvValueType::Uint,         123, number_of_navsats_in_view: u8;
+        PositionMethodSource                        => KlvValueType::Uint,         124, position_method_source: PositionMethodSource;
+        PlatformStatus                              => KlvValueType::Uint,         125, platform_status: PlatformStatus;
+        SensorControlMode                           => KlvValueType::Uint,         126, sensor_control_mode: SensorControlMode;
+        SensorFrameRatePack                         => KlvValueType::DLP,          127, sensor_frame_rate_pack: &[u8];
+        WavelengthsList                             => KlvValueType::VLP,          128, wavelengths_list: &[u8];
+        TargetId                                    => KlvValueType::Utf8,         129, target_id: &str;
+        AirbaseLocations                            => KlvValueType::VLP,          130, airbase_locations: &[u8];
+        TakeoffTime                                 => KlvValueType::Uint,         131, takeoff_time: u64;
+        TransmissionFrequency                       => KlvValueType::IMAPB,        132, transmission_frequency: Megahertz;
+        OnboardMiStorageCapacity                    => KlvValueType::Uint,         133, onboard_mi_storage_capacity: u64;
+        ZoomPercentage                              => KlvValueType::IMAPB,        134, zoom_percentage: Percent;
+        CommunicationsMethod                        => KlvValueType::Utf8,         135, communications_method: &str;
+        LeapSeconds                                 => KlvValueType::Int,          136, leap_seconds: i64;
+        CorrectionOffset                            => KlvValueType::Int,          137, correction_offset: i64;
+        PayloadList                                 => KlvValueType::VLP,          138, payload_list: &[u8];
+        ActivePayloads                              => KlvValueType::Byte,         139, active_payloads: ActivePayloads;
+        WeaponStores                                => KlvValueType::VLP,          140, weapon_stores: Vec<WeaponStore>;
+        WaypointList                                => KlvValueType::VLP,          141, waypoint_list: &[u8];
+        ViewDomain                                  => KlvValueType::VLP,          142, view_domain: &[u8];
+        MetadataSubstreamIdPack                     => KlvValueType::Byte,         143, metadata_substream_id_pack: &[u8];
     }
-}
\ No newline at end of file
+}
+
+/// How long the value of a tag may be, in bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Length {
+    /// The value is always exactly this long.
+    Fixed(usize),
+    /// The value is at most this long.
+    Max(usize),
+    /// The value is only limited by the length of the packet.
+    Variable,
+}
+
+/// Information about a tag from MISB ST 0601.19.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TagInfo {
+    /// Name of the item in the standard.
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Units of the value once mapped from its encoded form.
+    pub units: Option<&'static str>,
+    /// Smallest and largest value once mapped from its encoded form.
+    pub range: Option<(f64, f64)>,
+    pub length: Length,
+}
+
+impl Tag {
+    /// Information about this tag from MISB ST 0601.19.
+    #[rustfmt::skip]
+    pub fn info(self) -> TagInfo {
+        use Length::Fixed;
+        use Length::Max;
+        use Length::Variable;
+
+        const DEG: Option<&str> = Some("°");
+        const M: Option<&str> = Some("m");
+        const MPS: Option<&str> = Some("m/s");
+        const MBAR: Option<&str> = Some("mbar");
+        const US: Option<&str> = Some("µs");
+        const PCT: Option<&str> = Some("%");
+        const NONE: Option<&str> = None;
+        const LAT: Option<(f64, f64)> = Some((-90.0, 90.0));
+        const LON: Option<(f64, f64)> = Some((-180.0, 180.0));
+        const ALT: Option<(f64, f64)> = Some((-900.0, 19_000.0));
+        const ALT_EXT: Option<(f64, f64)> = Some((-900.0, 40_000.0));
+        const HEADING: Option<(f64, f64)> = Some((0.0, 360.0));
+        const OFFSET: Option<(f64, f64)> = Some((-0.075, 0.075));
+        const PRESSURE: Option<(f64, f64)> = Some((0.0, 5_000.0));
+        const RATE: Option<(f64, f64)> = Some((-1_000.0, 1_000.0));
+        const ANY: Option<(f64, f64)> = None;
+
+        let (name, description, units, range, length) = match self {
+            Tag::Checksum                                 => ("Checksum", "Checksum used to detect errors within a UAS Datalink LS packet", NONE, ANY, Fixed(2)),
+            Tag::PrecisionTimeStamp                       => ("Precision Time Stamp", "Timestamp for all metadata in this Local Set; used to coordinate with Motion Imagery", US, ANY, Fixed(8)),
+            Tag::MissionID                                => ("Mission ID", "Descriptive mission identifier to distinguish event or sortie", NONE, ANY, Max(127)),
+            Tag::PlatformTailNumber                       => ("Platform Tail Number", "Identifier of platform as posted", NONE, ANY, Max(127)),
+            Tag::PlatformHeadingAngle                     => ("Platform Heading Angle", "Aircraft heading angle", DEG, HEADING, Fixed(2)),
+            Tag::PlatformPitchAngle                       => ("Platform Pitch Angle", "Aircraft pitch angle", DEG, Some((-20.0, 20.0)), Fixed(2)),
+            Tag::PlatformRollAngle                        => ("Platform Roll Angle", "Platform roll angle", DEG, Some((-50.0, 50.0)), Fixed(2)),
+            Tag::PlatformTrueAirspeed                     => ("Platform True Airspeed", "True airspeed (TAS) of platform", MPS, Some((0.0, 255.0)), Fixed(1)),
+            Tag::PlatformIndicatedAirspeed                => ("Platform Indicated Airspeed", "Indicated airspeed (IAS) of platform", MPS, Some((0.0, 255.0)), Fixed(1)),
+            Tag::PlatformDesignation                      => ("Platform Designation", "Model name for the platform", NONE, ANY, Max(127)),
+            Tag::ImageSourceSensor                        => ("Image Source Sensor", "Name of currently active sensor", NONE, ANY, Max(127)),
+            Tag::ImageCoordinateSystem                    => ("Image Coordinate System", "Name of the image coordinate system used", NONE, ANY, Max(127)),
+            Tag::SensorLatitude                           => ("Sensor Latitude", "Sensor latitude", DEG, LAT, Fixed(4)),
+            Tag::SensorLongitude                          => ("Sensor Longitude", "Sensor longitude", DEG, LON, Fixed(4)),
+            Tag::SensorTrueAltitude                       => ("Sensor True Altitude", "Altitude of sensor as measured from Mean Sea Level (MSL)", M, ALT, Fixed(2)),
+            Tag::SensorHorizontalFieldOfView              => ("Sensor Horizontal Field of View", "Horizontal field of view of selected imaging sensor", DEG, Some((0.0, 180.0)), Fixed(2)),
+            Tag::SensorVerticalFieldOfView                => ("Sensor Vertical Field of View", "Vertical field of view of selected imaging sensor", DEG, Some((0.0, 180.0)), Fixed(2)),
+            Tag::SensorRelativeAzimuthAngle               => ("Sensor Relative Azimuth Angle", "Relative rotation angle of sensor to platform longitudinal axis", DEG, HEADING, Fixed(4)),
+            Tag::SensorRelativeElevationAngle             => ("Sensor Relative Elevation Angle", "Relative elevation angle of sensor to platform longitudinal-transverse plane", DEG, LON, Fixed(4)),
+            Tag::SensorRelativeRollAngle                  => ("Sensor Relative Roll Angle", "Relative roll angle of sensor to aircraft platform", DEG, HEADING, Fixed(4)),
+            Tag::SlantRange                               => ("Slant Range", "Slant range in meters", M, Some((0.0, 5_000_000.0)), Fixed(4)),
+            Tag::TargetWidth                              => ("Target Width", "Target width within sensor field of view", M, Some((0.0, 10_000.0)), Fixed(2)),
+            Tag::FrameCenterLatitude                      => ("Frame Center Latitude", "Terrain latitude of frame center", DEG, LAT, Fixed(4)),
+            Tag::FrameCenterLongitude                     => ("Frame Center Longitude", "Terrain longitude of frame center", DEG, LON, Fixed(4)),
+            Tag::FrameCenterElevation                     => ("Frame Center Elevation", "Terrain elevation at frame center relative to Mean Sea Level (MSL)", M, ALT, Fixed(2)),
+            Tag::OffsetCornerLatitudePoint1               => ("Offset Corner Latitude Point 1", "Frame latitude offset for upper left corner", DEG, OFFSET, Fixed(2)),
+            Tag::OffsetCornerLongitudePoint1              => ("Offset Corner Longitude Point 1", "Frame longitude offset for upper left corner", DEG, OFFSET, Fixed(2)),
+            Tag::OffsetCornerLatitudePoint2               => ("Offset Corner Latitude Point 2", "Frame latitude offset for upper right corner", DEG, OFFSET, Fixed(2)),
+            Tag::OffsetCornerLongitudePoint2              => ("Offset Corner Longitude Point 2", "Frame longitude offset for upper right corner", DEG, OFFSET, Fixed(2)),
+            Tag::OffsetCornerLatitudePoint3               => ("Offset Corner Latitude Point 3", "Frame latitude offset for lower right corner", DEG, OFFSET, Fixed(2)),
+            Tag::OffsetCornerLongitudePoint3              => ("Offset Corner Longitude Point 3", "Frame longitude offset for lower right corner", DEG, OFFSET, Fixed(2)),
+            Tag::OffsetCornerLatitudePoint4               => ("Offset Corner Latitude Point 4", "Frame latitude offset for lower left corner", DEG, OFFSET, Fixed(2)),
+            Tag::OffsetCornerLongitudePoint4              => ("Offset Corner Longitude Point 4", "Frame longitude offset for lower left corner", DEG, OFFSET, Fixed(2)),
+            Tag::IcingDetected                            => ("Icing Detected", "Flag for icing detected at aircraft location", NONE, ANY, Fixed(1)),
+            Tag::WindDirection                            => ("Wind Direction", "Wind direction at aircraft location", DEG, HEADING, Fixed(2)),
+            Tag::WindSpeed                                => ("Wind Speed", "Wind speed at aircraft location", MPS, Some((0.0, 100.0)), Fixed(1)),
+            Tag::StaticPressure                           => ("Static Pressure", "Static pressure at aircraft location", MBAR, PRESSURE, Fixed(2)),
+            Tag::DensityAltitude                          => ("Density Altitude", "Density altitude at aircraft location", M, ALT, Fixed(2)),
+            Tag::OutsideAirTemperature                    => ("Outside Air Temperature", "Temperature outside of aircraft", Some("°C"), Some((-128.0, 127.0)), Fixed(1)),
+            Tag::TargetLocationLatitude                   => ("Target Location Latitude", "Calculated target latitude", DEG, LAT, Fixed(4)),
+            Tag::TargetLocationLongitude                  => ("Target Location Longitude", "Calculated target longitude", DEG, LON, Fixed(4)),
+            Tag::TargetLocationElevation                  => ("Target Location Elevation", "Calculated target elevation", M, ALT, Fixed(2)),
+            Tag::TargetTrackGateWidth                     => ("Target Track Gate Width", "Tracking gate width (x value) of tracked target within field of view", Some("pixels"), Some((0.0, 510.0)), Fixed(1)),
+            Tag::TargetTrackGateHeight                    => ("Target Track Gate Height", "Tracking gate height (y value) of tracked target within field of view", Some("pixels"), Some((0.0, 510.0)), Fixed(1)),
+            Tag::TargetErrorEstimateCE90                  => ("Target Error Estimate - CE90", "Circular error 90 (CE90) is the estimated error distance in the horizontal direction", M, Some((0.0, 4_095.0)), Fixed(2)),
+            Tag::TargetErrorEstimateLe90                  => ("Target Error Estimate - LE90", "Linear error 90 (LE90) is the estimated error distance in the vertical (or height) direction", M, Some((0.0, 4_095.0)), Fixed(2)),
+            Tag::GenericFlagData                          => ("Generic Flag Data", "Generic metadata flags", NONE, ANY, Fixed(1)),
+            Tag::SecurityLocalSet                         => ("Security Local Set", "MISB ST 0102 local set Security Metadata items", NONE, ANY, Variable),
+            Tag::DifferentialPressure                     => ("Differential Pressure", "Differential pressure at aircraft location", MBAR, PRESSURE, Fixed(2)),
+            Tag::PlatformAngleOfAttack                    => ("Platform Angle of Attack", "Platform attack angle", DEG, Some((-20.0, 20.0)), Fixed(2)),
+            Tag::PlatformVerticalSpeed                    => ("Platform Vertical Speed", "Vertical speed of the aircraft relative to zenith", MPS, Some((-180.0, 180.0)), Fixed(2)),
+            Tag::PlatformSideslipAngle                    => ("Platform Sideslip Angle", "Angle between the platform longitudinal axis and relative wind", DEG, Some((-20.0, 20.0)), Fixed(2)),
+            Tag::AirfieldBarometricPressure               => ("Airfield Barometric Pressure", "Local pressure at airfield of known height", MBAR, PRESSURE, Fixed(2)),
+            Tag::AirfieldElevation                        => ("Airfield Elevation", "Elevation of airfield corresponding to Airfield Barometric Pressure", M, ALT, Fixed(2)),
+            Tag::RelativeHumidity                         => ("Relative Humidity", "Relative humidity at aircraft location", PCT, Some((0.0, 100.0)), Fixed(1)),
+            Tag::PlatformGroundSpeed                      => ("Platform Ground Speed", "Speed projected to the ground of an airborne platform passing overhead", MPS, Some((0.0, 255.0)), Fixed(1)),
+            Tag::GroundRange                              => ("Ground Range", "Horizontal distance from ground position of aircraft relative to nadir, and target of interest", M, Some((0.0, 5_000_000.0)), Fixed(4)),
+            Tag::PlatformFuelRemaining                    => ("Platform Fuel Remaining", "Remaining fuel on airborne platform", Some("kg"), Some((0.0, 10_000.0)), Fixed(2)),
+            Tag::PlatformCallSign                         => ("Platform Call Sign", "Call sign of platform or operating unit", NONE, ANY, Max(127)),
+            Tag::WeaponLoad                               => ("Weapon Load", "Current weapons stored on aircraft", NONE, ANY, Fixed(2)),
+            Tag::WeaponFired                              => ("Weapon Fired", "Indication when a particular weapon is released", NONE, ANY, Fixed(1)),
+            Tag::LaserPrfCode                             => ("Laser PRF Code", "A laser's Pulse Repetition Frequency (PRF) code used to mark a target", NONE, Some((1_111.0, 2_888.0)), Fixed(2)),
+            Tag::SensorFieldOfViewName                    => ("Sensor Field of View Name", "Names sensor field of view quantized steps", NONE, ANY, Fixed(1)),
+            Tag::PlatformMagneticHeading                  => ("Platform Magnetic Heading", "Aircraft magnetic heading angle", DEG, HEADING, Fixed(2)),
+            Tag::UasDatalinkLsVersionNumber               => ("UAS Datalink LS Version Number", "Version number of the UAS Datalink LS document used to generate KLV metadata", NONE, Some((0.0, 255.0)), Fixed(1)),
+            Tag::AlternatePlatformLatitude                => ("Alternate Platform Latitude", "Alternate platform latitude", DEG, LAT, Fixed(4)),
+            Tag::AlternatePlatformLongitude               => ("Alternate Platform Longitude", "Alternate platform longitude", DEG, LON, Fixed(4)),
+            Tag::AlternatePlatformAltitude                => ("Alternate Platform Altitude", "Altitude of alternate platform as measured from Mean Sea Level (MSL)", M, ALT, Fixed(2)),
+            Tag::AlternatePlatformName                    => ("Alternate Platform Name", "Name of alternate platform connected to UAS", NONE, ANY, Max(127)),
+            Tag::AlternatePlatformHeading                 => ("Alternate Platform Heading", "Heading angle of alternate platform connected to UAS", DEG, HEADING, Fixed(2)),
+            Tag::EventStartTime                           => ("Event Start Time - UTC", "Start time of scene, project, event, mission, editing event, license, publication, etc.", US, ANY, Fixed(8)),
+            Tag::RvtLocalSet                              => ("RVT Local Set", "MISB ST 0806 RVT Local Set metadata items", NONE, ANY, Variable),
+            Tag::VmtiLocalSet                             => ("VMTI Local Set", "MISB ST 0903 VMTI Local Set metadata items", NONE, ANY, Variable),
+            Tag::SensorEllipsoidHeight                    => ("Sensor Ellipsoid Height", "Sensor ellipsoid height as measured from the reference WGS84 ellipsoid", M, ALT, Fixed(2)),
+            Tag::AlternatePlatformEllipsoidHeight         => ("Alternate Platform Ellipsoid Height", "Alternate platform ellipsoid height as measured from the reference WGS84 ellipsoid", M, ALT, Fixed(2)),
+            Tag::OperationalMode                          => ("Operational Mode", "Indicates the mode of operations of the event portrayed in Motion Imagery", NONE, ANY, Fixed(1)),
+            Tag::FrameCenterHeightAboveEllipsoid          => ("Frame Center Height Above Ellipsoid", "Frame center ellipsoid height as measured from the reference WGS84 ellipsoid", M, ALT, Fixed(2)),
+            Tag::SensorNorthVelocity                      => ("Sensor North Velocity", "Northing velocity of the sensor or platform", MPS, Some((-327.0, 327.0)), Fixed(2)),
+            Tag::SensorEastVelocity                       => ("Sensor East Velocity", "Easting velocity of the sensor or platform", MPS, Some((-327.0, 327.0)), Fixed(2)),
+            Tag::ImageHorizonPixelPack                    => ("Image Horizon Pixel Pack", "Location of earth-sky horizon in the Imagery", NONE, ANY, Variable),
+            Tag::CornerLatitudePoint1Full                 => ("Corner Latitude Point 1 (Full)", "Frame latitude for upper left corner", DEG, LAT, Fixed(4)),
+            Tag::CornerLongitudePoint1Full                => ("Corner Longitude Point 1 (Full)", "Frame longitude for upper left corner", DEG, LON, Fixed(4)),
+            Tag::CornerLatitudePoint2Full                 => ("Corner Latitude Point 2 (Full)", "Frame latitude for upper right corner", DEG, LAT, Fixed(4)),
+            Tag::CornerLongitudePoint2Full                => ("Corner Longitude Point 2 (Full)", "Frame longitude for upper right corner", DEG, LON, Fixed(4)),
+            Tag::CornerLatitudePoint3Full                 => ("Corner Latitude Point 3 (Full)", "Frame latitude for lower right corner", DEG, LAT, Fixed(4)),
+            Tag::CornerLongitudePoint3Full                => ("Corner Longitude Point 3 (Full)", "Frame longitude for lower right corner", DEG, LON, Fixed(4)),
+            Tag::CornerLatitudePoint4Full                 => ("Corner Latitude Point 4 (Full)", "Frame latitude for lower left corner", DEG, LAT, Fixed(4)),
+            Tag::CornerLongitudePoint4Full                => ("Corner Longitude Point 4 (Full)", "Frame longitude for lower left corner", DEG, LON, Fixed(4)),
+            Tag::PlatformPitchAngleFull                   => ("Platform Pitch Angle (Full)", "Aircraft pitch angle", DEG, LAT, Fixed(4)),
+            Tag::PlatformRollAngleFull                    => ("Platform Roll Angle (Full)", "Platform roll angle", DEG, LAT, Fixed(4)),
+            Tag::PlatformAngleOfAttackFull                => ("Platform Angle of Attack (Full)", "Platform attack angle", DEG, LAT, Fixed(4)),
+            Tag::PlatformSideslipAngleFull                => ("Platform Sideslip Angle (Full)", "Angle between the platform longitudinal axis and relative wind", DEG, LON, Fixed(4)),
+            Tag::MiisCoreIdentifier                       => ("MIIS Core Identifier", "MISB ST 1204 MIIS Core Identifier binary value", NONE, ANY, Max(50)),
+            Tag::SarMotionImageryLocalSet                 => ("SAR Motion Imagery Local Set", "MISB ST 1206 SAR Motion Imagery Metadata Local Set metadata items", NONE, ANY, Variable),
+            Tag::TargetWidthExtended                      => ("Target Width Extended", "Target width within sensor field of view", M, Some((0.0, 1_500_000.0)), Max(8)),
+            Tag::RangeImageLocalSet                       => ("Range Image Local Set", "MISB ST 1002 Range Imaging Local Set metadata items", NONE, ANY, Variable),
+            Tag::GeoRegistrationLocalSet                  => ("Geo-Registration Local Set", "MISB ST 1601 Geo-Registration Local Set metadata items", NONE, ANY, Variable),
+            Tag::CompositeImagingLocalSet                 => ("Composite Imaging Local Set", "MISB ST 1602 Composite Imaging Local Set metadata items", NONE, ANY, Variable),
+            Tag::SegmentLocalSet                          => ("Segment Local Set", "MISB ST 1607 Segment Local Set metadata items, used to enable metadata sharing", NONE, ANY, Variable),
+            Tag::AmendLocalSet                            => ("Amend Local Set", "MISB ST 1607 Amend Local Set metadata items, used to provide metadata corrections", NONE, ANY, Variable),
+            Tag::SdccFlp                                  => ("SDCC-FLP", "MISB ST 1010 Floating Length Pack (FLP) metadata item, providing standard deviation and cross correlation (SDCC) metadata", NONE, ANY, Variable),
+            Tag::DensityAltitudeExtended                  => ("Density Altitude Extended", "Density altitude above MSL at aircraft location", M, ALT_EXT, Max(8)),
+            Tag::SensorEllipsoidHeightExtended            => ("Sensor Ellipsoid Height Extended", "Sensor ellipsoid height extended as measured from the reference WGS84 ellipsoid", M, ALT_EXT, Max(8)),
+            Tag::AlternatePlatformEllipsoidHeightExtended => ("Alternate Platform Ellipsoid Height Extended", "Alternate platform ellipsoid height extended as measured from the reference WGS84 ellipsoid", M, ALT_EXT, Max(8)),
+            Tag::StreamDesignator                         => ("Stream Designator", "A second designation given to a sortie", NONE, ANY, Max(127)),
+            Tag::OperationalBase                          => ("Operational Base", "Name of the operational base hosting the platform", NONE, ANY, Max(127)),
+            Tag::BroadcastSource                          => ("Broadcast Source", "Name of the source, where the Motion Imagery is first broadcast", NONE, ANY, Max(127)),
+            Tag::RangeToRecoveryLocation                  => ("Range To Recovery Location", "Distance from current position to airframe recovery position", Some("km"), Some((0.0, 21_000.0)), Max(8)),
+            Tag::TimeAirborne                             => ("Time Airborne", "Number of seconds of aircraft airborne time", Some("s"), ANY, Max(4)),
+            Tag::PropulsionUnitSpeed                      => ("Propulsion Unit Speed", "The speed the engine (or electric motor) is rotating at", Some("RPM"), ANY, Max(4)),
+            Tag::PlatformCourseAngle                      => ("Platform Course Angle", "Direction the aircraft is moving relative to True North", DEG, HEADING, Max(8)),
+            Tag::AltitudeAgl                              => ("Altitude AGL", "Above Ground Level (AGL) height above the ground/water", M, ALT_EXT, Max(8)),
+            Tag::RadarAltimeter                           => ("Radar Altimeter", "Height above the ground/water as reported by a RADAR altimeter", M, ALT_EXT, Max(8)),
+            Tag::ControlCommand                           => ("Control Command", "Record of command from GCS to Aircraft", NONE, ANY, Variable),
+            Tag::ControlCommandVerificationList           => ("Control Command Verification List", "Acknowledgement of one or more control commands were received by the platform", NONE, ANY, Variable),
+            Tag::SensorAzimuthRate                        => ("Sensor Azimuth Rate", "The rate the sensors azimuth angle is changing", Some("°/s"), RATE, Max(8)),
+            Tag::SensorElevationRate                      => ("Sensor Elevation Rate", "The rate the sensors elevation angle is changing", Some("°/s"), RATE, Max(8)),
+            Tag::SensorRollRate                           => ("Sensor Roll Rate", "The rate the sensors roll angle is changing", Some("°/s"), RATE, Max(8)),
+            Tag::OnboardMiStoragePercentFull              => ("On-board MI Storage Percent Full", "Amount of on-board Motion Imagery storage used as a percentage of the total storage", PCT, Some((0.0, 100.0)), Max(8)),
+            Tag::ActiveWaypointList                       => ("Active Wavelength List", "List of wavelengths in Motion Imagery", NONE, ANY, Variable),
+            Tag::CountryCodes                             => ("Country Codes", "Country codes which are associated with the platform and its operation", NONE, ANY, Variable),
+            Tag::NumberOfNavsatsInView                    => ("Number of NAVSATs in View", "Count of navigation satellites in view of platform", NONE, Some((0.0, 255.0)), Max(1)),
+            Tag::PositionMethodSource                     => ("Positioning Method Source", "Source of the navigation positioning information", NONE, ANY, Max(1)),
+            Tag::PlatformStatus                           => ("Platform Status", "Enumeration of operational modes of the platform", NONE, ANY, Max(1)),
+            Tag::SensorControlMode                        => ("Sensor Control Mode", "Enumerated value for the current sensor control operational status", NONE, ANY, Max(1)),
+            Tag::SensorFrameRatePack                      => ("Sensor Frame Rate Pack", "Values used to compute the frame rate of the Motion Imagery at the sensor", NONE, ANY, Variable),
+            Tag::WavelengthsList                          => ("Wavelengths List", "List of wavelength identifiers", NONE, ANY, Variable),
+            Tag::TargetId                                 => ("Target ID", "Alpha-numeric identification of a target", NONE, ANY, Max(32)),
+            Tag::AirbaseLocations                         => ("Airbase Locations", "Geographic location of the take-off site and recovery site", NONE, ANY, Variable),
+            Tag::TakeoffTime                              => ("Take-off Time", "Time when aircraft became airborne", US, ANY, Max(8)),
+            Tag::TransmissionFrequency                    => ("Transmission Frequency", "Radio frequency used to transmit the Motion Imagery", Some("MHz"), Some((1.0, 99_999.0)), Max(8)),
+            Tag::OnboardMiStorageCapacity                 => ("On-board MI Storage Capacity", "The total capacity of on-board Motion Imagery storage", Some("GB"), ANY, Max(4)),
+            Tag::ZoomPercentage                           => ("Zoom Percentage", "For a variable zoom system, the percentage of zoom", PCT, Some((0.0, 100.0)), Max(8)),
+            Tag::CommunicationsMethod                     => ("Communications Method", "Type of communications used with platform", NONE, ANY, Max(127)),
+            Tag::LeapSeconds                              => ("Leap Seconds", "Number of leap seconds to adjust Precision Time Stamp (Tag 2) to UTC", Some("s"), ANY, Max(4)),
+            Tag::CorrectionOffset                         => ("Correction Offset", "Post-flight time adjustment to correct Precision Time Stamp (Tag 2) as needed", US, ANY, Max(8)),
+            Tag::PayloadList                              => ("Payload List", "List of payloads available on the platform", NONE, ANY, Variable),
+            Tag::ActivePayloads                           => ("Active Payloads", "List of currently active payloads from the payload list (Tag 138)", NONE, ANY, Variable),
+            Tag::WeaponStores                             => ("Weapons Stores", "List of weapon stores and status", NONE, ANY, Variable),
+            Tag::WaypointList                             => ("Waypoint List", "List of waypoints and their status", NONE, ANY, Variable),
+            Tag::ViewDomain                               => ("View Domain", "Specifies the domain of values for Relative Sensor Azimuth, Elevation and Roll Angles", NONE, ANY, Variable),
+            Tag::MetadataSubstreamIdPack                  => ("Metadata Substream ID Pack", "Identifier for identifying sub-streams of metadata", NONE, ANY, Variable),
+            Tag::Deprecated                               => ("Deprecated", "Item no longer used in MISB ST 0601", NONE, ANY, Variable),
+            _                                             => ("Unknown", "Item not defined in MISB ST 0601", NONE, ANY, Variable),
+        };
+
+        TagInfo { name, description, units, range, length }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use test_case::test_case;
+
+    use super::Length;
+    use super::Tag;
+    use crate::klv_value::KlvValueType;
+
+    #[test_case("13", Tag::SensorLatitude; "ID")]
+    #[test_case("SensorLatitude", Tag::SensorLatitude; "Name")]
+    #[test_case("sensorlatitude", Tag::SensorLatitude; "Lower case name")]
+    #[test_case("143", Tag::MetadataSubstreamIdPack; "Last ID")]
+    fn from_str(s: &str, tag: Tag) {
+        assert_eq!(s.parse::<Tag>().unwrap(), tag);
+        assert_eq!(tag.to_string().parse::<Tag>().unwrap(), tag);
+    }
+
+    #[test_case("144"; "ID out of range")]
+    #[test_case("Sensor Latitude"; "Name from the standard")]
+    #[test_case(""; "Empty")]
+    fn from_str_invalid(s: &str) {
+        assert!(s.parse::<Tag>().is_err());
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(Tag::Checksum < Tag::PrecisionTimeStamp);
+        assert!(Tag::iter().zip(Tag::iter().skip(1)).all(|(a, b)| a < b));
+        assert_eq!(Tag::iter().collect::<HashSet<_>>().len(), Tag::COUNT);
+    }
+
+    #[test]
+    fn info_length() {
+        for tag in Tag::iter() {
+            let size = match tag.tag_type() {
+                KlvValueType::Int8 | KlvValueType::Uint8 => 1,
+                KlvValueType::Int16 | KlvValueType::Uint16 => 2,
+                KlvValueType::Int32 | KlvValueType::Uint32 => 4,
+                KlvValueType::Uint64 => 8,
+                _ => continue,
+            };
+            assert_eq!(tag.info().length, Length::Fixed(size), "{}", tag);
+        }
+        assert_eq!(Tag::SensorLatitude.info().range, Some((-90.0, 90.0)));
+        assert_eq!(Tag::SensorLatitude.info().units, Some("°"));
+    }
+}
@@ -0,0 +1,446 @@
+//! Interpolation of metadata between the packets of a recording.
+//!
+//! KLV packets rarely arrive at the frame rate of the video they describe.
+//! A [`MetadataTrack`] collects the values of every tag over time so they can
+//! be read at any precision time stamp, or resampled to a fixed rate.
+//!
+//! Values are interpolated in the units they are encoded in, which are linear
+//! in the real world value for every tag that is interpolated.
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::klv_packet::KlvPacket;
+use crate::klv_value::KlvValue;
+use crate::klv_value::KlvValueType;
+use crate::tag::Tag;
+use crate::units;
+
+/// How the value of a tag is estimated between two packets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Straight line between the two values.
+    Linear,
+    /// Straight line along the shortest way around the circle, for headings,
+    /// azimuths and longitudes that wrap around.
+    Angular,
+    /// The value of the earlier packet, for strings, flags and enumerations.
+    Hold,
+}
+
+impl Interpolation {
+    /// The interpolation used for the values of `tag`.
+    pub fn for_tag(tag: Tag) -> Interpolation {
+        let numeric = matches!(
+            tag.tag_type(),
+            KlvValueType::Int
+                | KlvValueType::Int8
+                | KlvValueType::Int16
+                | KlvValueType::Int32
+                | KlvValueType::Uint
+                | KlvValueType::Uint8
+                | KlvValueType::Uint16
+                | KlvValueType::Uint32
+                | KlvValueType::Uint64
+                | KlvValueType::IMAPB
+        );
+        if !numeric {
+            return Interpolation::Hold;
+        }
+
+        match tag {
+            Tag::PlatformHeadingAngle
+            | Tag::SensorLongitude
+            | Tag::SensorRelativeAzimuthAngle
+            | Tag::SensorRelativeRollAngle
+            | Tag::FrameCenterLongitude
+            | Tag::WindDirection
+            | Tag::TargetLocationLongitude
+            | Tag::PlatformMagneticHeading
+            | Tag::AlternatePlatformLongitude
+            | Tag::AlternatePlatformHeading
+            | Tag::CornerLongitudePoint1Full
+            | Tag::CornerLongitudePoint2Full
+            | Tag::CornerLongitudePoint3Full
+            | Tag::CornerLongitudePoint4Full
+            | Tag::PlatformCourseAngle => Interpolation::Angular,
+            Tag::IcingDetected
+            | Tag::GenericFlagData
+            | Tag::WeaponLoad
+            | Tag::WeaponFired
+            | Tag::LaserPrfCode
+            | Tag::SensorFieldOfViewName
+            | Tag::UasDatalinkLsVersionNumber
+            | Tag::EventStartTime
+            | Tag::OperationalMode
+            | Tag::NumberOfNavsatsInView
+            | Tag::PositionMethodSource
+            | Tag::PlatformStatus
+            | Tag::SensorControlMode
+            | Tag::TakeoffTime
+            | Tag::LeapSeconds
+            | Tag::CorrectionOffset => Interpolation::Hold,
+            _ => Interpolation::Linear,
+        }
+    }
+}
+
+/// The values of every tag at one point in time.
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub timestamp: u64,
+    pub values: Vec<(Tag, KlvValue)>,
+}
+
+impl Sample {
+    pub fn get(&self, tag: Tag) -> Option<&KlvValue> {
+        self.values.iter().find(|(t, _)| *t == tag).map(|(_, value)| value)
+    }
+}
+
+/// The values of every tag in a sequence of packets, ordered by precision
+/// time stamp.
+#[derive(Clone, Debug, Default)]
+pub struct MetadataTrack {
+    series: BTreeMap<usize, Vec<(u64, KlvValue)>>,
+    start: Option<u64>,
+    end: Option<u64>,
+}
+
+impl MetadataTrack {
+    /// Build a track from packets in any order. Packets without a precision
+    /// time stamp are ignored and checksums are not kept.
+    pub fn new<'a>(
+        packets: impl IntoIterator<Item = &'a KlvPacket>,
+    ) -> MetadataTrack {
+        let mut track = MetadataTrack::default();
+        for packet in packets {
+            let Some(&KlvValue::Uint64(timestamp)) = packet
+                .get_ref(Tag::PrecisionTimeStamp)
+                .map(|field| field.value())
+            else {
+                continue;
+            };
+            track.start = Some(
+                track.start.map_or(timestamp, |start| start.min(timestamp)),
+            );
+            track.end = track.end.max(Some(timestamp));
+
+            for field in packet.fields() {
+                if field.tag() == Tag::Checksum {
+                    continue;
+                }
+                track
+                    .series
+                    .entry(field.tag().id())
+                    .or_default()
+                    .push((timestamp, field.value().clone()));
+            }
+        }
+
+        for series in track.series.values_mut() {
+            // Keep the order packets arrived in for equal time stamps so the
+            // latest one wins.
+            series.sort_by_key(|(timestamp, _)| *timestamp);
+        }
+        track
+    }
+
+    /// Precision time stamp of the first packet.
+    pub fn start(&self) -> Option<u64> {
+        self.start
+    }
+
+    /// Precision time stamp of the last packet.
+    pub fn end(&self) -> Option<u64> {
+        self.end
+    }
+
+    /// Every tag that appears in the track.
+    pub fn tags(&self) -> impl Iterator<Item = Tag> + '_ {
+        self.series.keys().map(|id| Tag::from(*id))
+    }
+
+    /// Estimate the value of `tag` at `timestamp`.
+    ///
+    /// Returns `None` before the first value of the tag. After the last value
+    /// only tags that are held keep their value.
+    pub fn value_at(&self, tag: Tag, timestamp: u64) -> Option<KlvValue> {
+        let series = self.series.get(&tag.id())?;
+        // Index of the first value after `timestamp`.
+        let next = series.partition_point(|(t, _)| *t <= timestamp);
+        let (before_time, before) = series.get(next.checked_sub(1)?)?;
+        if *before_time == timestamp {
+            return Some(before.clone());
+        }
+
+        let interpolation = Interpolation::for_tag(tag);
+        if interpolation == Interpolation::Hold {
+            return Some(before.clone());
+        }
+        let (after_time, after) = series.get(next)?;
+        // An error indicator isn't a measurement to interpolate towards.
+        if units::is_error(tag, before) || units::is_error(tag, after) {
+            return Some(before.clone());
+        }
+
+        let fraction = Fraction {
+            numerator: (timestamp - before_time) as i128,
+            denominator: (after_time - before_time) as i128,
+        };
+        interpolate(before, after, fraction, interpolation)
+            .or_else(|| Some(before.clone()))
+    }
+
+    /// Estimate the value of every tag at `timestamp`.
+    pub fn sample_at(&self, timestamp: u64) -> Sample {
+        let values = self
+            .tags()
+            .filter_map(|tag| Some((tag, self.value_at(tag, timestamp)?)))
+            .collect();
+        Sample { timestamp, values }
+    }
+
+    /// Sample the track `rate` times a second from its first packet to its
+    /// last.
+    pub fn resample(&self, rate: f64) -> impl Iterator<Item = Sample> + '_ {
+        let period = 1_000_000.0 / rate;
+        let (start, end) = match (self.start, self.end) {
+            (Some(start), Some(end)) if period.is_finite() && period > 0.0 => {
+                (start, end)
+            }
+            _ => (1, 0),
+        };
+
+        // Calculate each time stamp from the start so rounding doesn't drift,
+        // stopping at the end of time rather than overflowing.
+        (0u64..)
+            .map_while(move |idx| {
+                start.checked_add(libm::round(idx as f64 * period) as u64)
+            })
+            .take_while(move |timestamp| *timestamp <= end)
+            .map(|timestamp| self.sample_at(timestamp))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Fraction {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Fraction {
+    /// `value` scaled by the fraction, rounded to the nearest integer.
+    ///
+    /// The product of two 64 bit values can overflow, in which case the
+    /// value is scaled as a float instead.
+    fn of(self, value: i128) -> i128 {
+        value
+            .checked_mul(self.numerator * 2)
+            .and_then(|scaled| scaled.checked_add(self.denominator))
+            .map(|scaled| scaled.div_euclid(self.denominator * 2))
+            .unwrap_or_else(|| {
+                libm::round(value as f64 * self.as_f64()) as i128
+            })
+    }
+
+    fn as_f64(self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+}
+
+/// Interpolate between two values of the same type, or `None` if the values
+/// can't be interpolated.
+fn interpolate(
+    before: &KlvValue,
+    after: &KlvValue,
+    fraction: Fraction,
+    interpolation: Interpolation,
+) -> Option<KlvValue> {
+    if let (KlvValue::IMAPB(a), KlvValue::IMAPB(b)) = (before, after) {
+        let mut difference = b - a;
+        if interpolation == Interpolation::Angular {
+            difference = libm::remainder(difference, 360.0);
+        }
+        let mut value = a + difference * fraction.as_f64();
+        if interpolation == Interpolation::Angular {
+            value -= 360.0 * libm::floor(value / 360.0);
+        }
+        return Some(KlvValue::IMAPB(value));
+    }
+
+    // Integers are interpolated exactly. The range of each angular type maps
+    // to a full circle.
+    let (a, b, min, max) = match (before, after) {
+        (KlvValue::Int(a), KlvValue::Int(b)) => {
+            (*a as i128, *b as i128, i64::MIN as i128, i64::MAX as i128)
+        }
+        (KlvValue::Int8(a), KlvValue::Int8(b)) => {
+            (*a as i128, *b as i128, i8::MIN as i128, i8::MAX as i128)
+        }
+        (KlvValue::Int16(a), KlvValue::Int16(b)) => {
+            (*a as i128, *b as i128, i16::MIN as i128, i16::MAX as i128)
+        }
+        (KlvValue::Int32(a), KlvValue::Int32(b)) => {
+            (*a as i128, *b as i128, i32::MIN as i128, i32::MAX as i128)
+        }
+        (KlvValue::Uint(a), KlvValue::Uint(b)) => {
+            (*a as i128, *b as i128, 0, u64::MAX as i128)
+        }
+        (KlvValue::Uint8(a), KlvValue::Uint8(b)) => {
+            (*a as i128, *b as i128, 0, u8::MAX as i128)
+        }
+        (KlvValue::Uint16(a), KlvValue::Uint16(b)) => {
+            (*a as i128, *b as i128, 0, u16::MAX as i128)
+        }
+        (KlvValue::Uint32(a), KlvValue::Uint32(b)) => {
+            (*a as i128, *b as i128, 0, u32::MAX as i128)
+        }
+        (KlvValue::Uint64(a), KlvValue::Uint64(b)) => {
+            (*a as i128, *b as i128, 0, u64::MAX as i128)
+        }
+        _ => return None,
+    };
+
+    let value = match interpolation {
+        Interpolation::Angular => {
+            // Signed angles are symmetric about zero so the most negative
+            // value, which is reserved, isn't part of the circle.
+            let low = if min < 0 { -max } else { 0 };
+            let circle = max - low;
+            let mut difference = (b - a).rem_euclid(circle);
+            if difference * 2 > circle {
+                difference -= circle;
+            }
+            (a + fraction.of(difference) - low).rem_euclid(circle) + low
+        }
+        _ => a + fraction.of(b - a),
+    };
+
+    Some(match before {
+        KlvValue::Int(_) => KlvValue::Int(value as i64),
+        KlvValue::Int8(_) => KlvValue::Int8(value as i8),
+        KlvValue::Int16(_) => KlvValue::Int16(value as i16),
+        KlvValue::Int32(_) => KlvValue::Int32(value as i32),
+        KlvValue::Uint(_) => KlvValue::Uint(value as u64),
+        KlvValue::Uint8(_) => KlvValue::Uint8(value as u8),
+        KlvValue::Uint16(_) => KlvValue::Uint16(value as u16),
+        KlvValue::Uint32(_) => KlvValue::Uint32(value as u32),
+        _ => KlvValue::Uint64(value as u64),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::Fraction;
+    use super::MetadataTrack;
+    use crate::klv_packet::tests::packet_at;
+    use crate::klv_value::KlvValue;
+    use crate::tag::Tag;
+
+    fn track() -> MetadataTrack {
+        MetadataTrack::new(&[
+            // Heading of 350 degrees, latitude of 0, mission ID of `A` and a
+            // longitude of 179 degrees.
+            packet_at(
+                1_000_000,
+                &[
+                    0x05, 0x02, 0xF8, 0xE3, 0x0D, 0x04, 0x00, 0x00, 0x00, 0x00,
+                    0x03, 0x01, b'A', 0x0E, 0x04, 0x7F, 0x49, 0xF4, 0x9E,
+                ],
+            ),
+            // Heading of 10 degrees, latitude of 1000, mission ID of `B` and a
+            // longitude of -179 degrees.
+            packet_at(
+                3_000_000,
+                &[
+                    0x05, 0x02, 0x07, 0x1C, 0x0D, 0x04, 0x00, 0x00, 0x03, 0xE8,
+                    0x03, 0x01, b'B', 0x0E, 0x04, 0x80, 0xB6, 0x0B, 0x62,
+                ],
+            ),
+        ])
+    }
+
+    #[test_case(Tag::SensorLatitude, 2_000_000, "Int32(500)"; "linear")]
+    #[test_case(Tag::SensorLatitude, 1_500_000, "Int32(250)"; "quarter")]
+    #[test_case(Tag::PlatformHeadingAngle, 2_000_000, "Uint16(0)"; "heading wraps")]
+    #[test_case(Tag::PlatformHeadingAngle, 2_500_000, "Uint16(910)"; "after north")]
+    #[test_case(Tag::SensorLongitude, 2_000_000, "Int32(-2147483647)"; "antimeridian")]
+    #[test_case(Tag::MissionID, 2_999_999, "Utf8(\"A\")"; "hold")]
+    #[test_case(Tag::MissionID, 5_000_000, "Utf8(\"B\")"; "hold after end")]
+    #[test_case(Tag::PrecisionTimeStamp, 1_234_567, "Uint64(1234567)"; "timestamp")]
+    fn value_at(tag: Tag, timestamp: u64, expected: &str) {
+        let value = track().value_at(tag, timestamp).unwrap();
+        assert_eq!(format!("{:?}", value), expected);
+    }
+
+    #[test]
+    fn error_indicator() {
+        // Latitudes of 0, the error indicator and 1000.
+        let track = MetadataTrack::new(&[
+            packet_at(1_000_000, &[0x0D, 0x04, 0x00, 0x00, 0x00, 0x00]),
+            packet_at(2_000_000, &[0x0D, 0x04, 0x80, 0x00, 0x00, 0x00]),
+            packet_at(3_000_000, &[0x0D, 0x04, 0x00, 0x00, 0x03, 0xE8]),
+        ]);
+        let latitude = |timestamp| {
+            format!(
+                "{:?}",
+                track.value_at(Tag::SensorLatitude, timestamp).unwrap()
+            )
+        };
+        assert_eq!(latitude(1_500_000), "Int32(0)");
+        assert_eq!(latitude(2_500_000), "Int32(-2147483648)");
+    }
+
+    #[test]
+    fn large_fraction() {
+        // Half way between time stamps far apart overflows an i128.
+        let fraction = Fraction { numerator: 1 << 63, denominator: 1 << 64 };
+        assert_eq!(fraction.of(u64::MAX as i128), 1 << 63);
+        let fraction = Fraction { numerator: 1, denominator: 4 };
+        assert_eq!(fraction.of(-6), -1);
+    }
+
+    #[test]
+    fn out_of_range() {
+        let track = track();
+        assert!(track.value_at(Tag::SensorLatitude, 999_999).is_none());
+        assert!(track.value_at(Tag::SensorLatitude, 3_000_001).is_none());
+        assert!(track.value_at(Tag::Checksum, 1_000_000).is_none());
+    }
+
+    #[test]
+    fn resample() {
+        let samples = track().resample(1.5).collect::<Vec<_>>();
+        let timestamps =
+            samples.iter().map(|sample| sample.timestamp).collect::<Vec<_>>();
+        assert_eq!(timestamps, [1_000_000, 1_666_667, 2_333_333, 3_000_000]);
+        assert!(matches!(
+            samples[2].get(Tag::SensorLatitude),
+            Some(KlvValue::Int32(667))
+        ));
+    }
+
+    #[test]
+    fn resample_tiny_rate() {
+        let timestamps = track()
+            .resample(1e-15)
+            .map(|sample| sample.timestamp)
+            .collect::<Vec<_>>();
+        assert_eq!(timestamps, [1_000_000]);
+    }
+
+    #[test]
+    fn resample_end_of_time() {
+        let track = MetadataTrack::new(&[
+            packet_at(u64::MAX - 1, &[0x03, 0x01, b'A']),
+            packet_at(u64::MAX, &[0x03, 0x01, b'B']),
+        ]);
+        let timestamps = track
+            .resample(1_000_000.0)
+            .map(|sample| sample.timestamp)
+            .collect::<Vec<_>>();
+        assert_eq!(timestamps, [u64::MAX - 1, u64::MAX]);
+    }
+}
@@ -0,0 +1,203 @@
+//! Values converted from their encoded form into engineering units.
+//!
+//! MISB ST 0601 maps most measurements onto the full range of an integer.
+//! The range of each tag is taken from [`Tag::info`], so the typed accessors
+//! on [`KlvPacket`] return the value in the units listed there.
+//!
+//! [`KlvPacket`]: crate::klv_packet::KlvPacket
+use alloc::vec::Vec;
+use core::fmt::Display;
+use core::fmt::Formatter;
+
+use crate::klv::FromKlv;
+use crate::klv::Klv;
+use crate::klv_value::KlvValue;
+use crate::klv_value::KlvValueType;
+use crate::tag::Tag;
+
+macro_rules! def_units {
+    ($($(#[$attr:meta])* $name:ident => $symbol:expr;)+) => {
+        $(
+            $(#[$attr])*
+            #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+            pub struct $name(pub f64);
+
+            impl Display for $name {
+                fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                    write!(f, "{} {}", self.0, $symbol)
+                }
+            }
+
+            impl FromKlv<'_> for $name {
+                fn from_klv(field: &Klv) -> Option<Self> {
+                    map(field).map($name)
+                }
+            }
+        )+
+    };
+}
+
+def_units! {
+    Degrees => "°";
+    DegreesPerSecond => "°/s";
+    Meters => "m";
+    Kilometers => "km";
+    MetersPerSecond => "m/s";
+    Millibars => "mbar";
+    Celsius => "°C";
+    Percent => "%";
+    Kilograms => "kg";
+    Megahertz => "MHz";
+    Pixels => "pixels";
+}
+
+/// Map the value of `field` onto the range of its tag.
+///
+/// Unsigned integers are mapped from zero to the minimum of the range and
+/// from their largest value to the maximum. Signed integers are mapped
+/// around zero and their most negative value means the value is invalid.
+/// Integers whose range is the same as the range of their type aren't
+/// mapped.
+fn map(field: &Klv) -> Option<f64> {
+    let (raw, type_min, type_max) = match *field.value() {
+        KlvValue::IMAPB(value) => return Some(value),
+        KlvValue::Uint8(raw) => (raw.into(), 0.0, u8::MAX.into()),
+        KlvValue::Uint16(raw) => (raw.into(), 0.0, u16::MAX.into()),
+        KlvValue::Uint32(raw) => (raw.into(), 0.0, u32::MAX.into()),
+        KlvValue::Int8(raw) => (raw.into(), i8::MIN.into(), i8::MAX.into()),
+        KlvValue::Int16(raw) => (raw.into(), i16::MIN.into(), i16::MAX.into()),
+        KlvValue::Int32(raw) => (raw.into(), i32::MIN.into(), i32::MAX.into()),
+        _ => return None,
+    };
+    let (min, max) = field.tag().info().range?;
+
+    if (min, max) == (type_min, type_max) {
+        Some(raw)
+    } else if type_min < 0.0 {
+        (!is_error(field.tag(), field.value())).then(|| raw * max / type_max)
+    } else {
+        Some(min + raw * (max - min) / type_max)
+    }
+}
+
+/// Whether `value` is the error indicator of `tag`, the most negative value
+/// of a signed integer that is mapped onto the range of the tag.
+pub(crate) fn is_error(tag: Tag, value: &KlvValue) -> bool {
+    let (raw, type_min, type_max): (f64, f64, f64) = match *value {
+        KlvValue::Int8(raw) => (raw.into(), i8::MIN.into(), i8::MAX.into()),
+        KlvValue::Int16(raw) => (raw.into(), i16::MIN.into(), i16::MAX.into()),
+        KlvValue::Int32(raw) => (raw.into(), i32::MIN.into(), i32::MAX.into()),
+        _ => return false,
+    };
+    let mapped =
+        tag.info().range.is_some_and(|range| range != (type_min, type_max));
+    mapped && raw == type_min
+}
+
+/// Encode `value`, in the units of `tag`, as the raw value of the tag.
+///
+/// This is the inverse of [`map`]. Values outside the range of the tag are
+/// clamped to it. Returns `None` if the tag isn't a fixed length integer.
+pub(crate) fn unmap(tag: Tag, value: f64) -> Option<Vec<u8>> {
+    let (size, type_min, type_max) = match tag.tag_type() {
+        KlvValueType::Uint8 => (1, 0.0, u8::MAX.into()),
+        KlvValueType::Uint16 => (2, 0.0, u16::MAX.into()),
+        KlvValueType::Uint32 => (4, 0.0, u32::MAX.into()),
+        KlvValueType::Int8 => (1, i8::MIN.into(), i8::MAX.into()),
+        KlvValueType::Int16 => (2, i16::MIN.into(), i16::MAX.into()),
+        KlvValueType::Int32 => (4, i32::MIN.into(), i32::MAX.into()),
+        _ => return None,
+    };
+    let (min, max) = tag.info().range?;
+
+    let raw = if (min, max) == (type_min, type_max) {
+        value
+    } else if type_min < 0.0 {
+        // The most negative value is reserved as an error indicator.
+        (value * type_max / max).max(-type_max)
+    } else {
+        (value - min) * type_max / (max - min)
+    };
+    let raw = libm::round(raw).clamp(type_min, type_max) as i64;
+
+    Some(raw.to_be_bytes()[8 - size..].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::map;
+    use super::unmap;
+    use crate::imap::to_imapb;
+    use crate::klv::Klv;
+    use crate::klv_packet::KlvPacket;
+    use crate::klv_packet::tests::packet_at;
+    use crate::tag::Tag;
+
+    fn packet(field: &[u8]) -> KlvPacket {
+        packet_at(1, field)
+    }
+
+    #[test_case(&[0x05, 0x02, 0x80, 0x00], 180.0; "Heading")]
+    #[test_case(&[0x05, 0x02, 0xFF, 0xFF], 360.0; "Largest heading")]
+    fn unsigned(field: &[u8], degrees: f64) {
+        let heading = packet(field).platform_heading_angle().unwrap();
+        assert!((heading.0 - degrees).abs() < 0.01, "{}", heading);
+    }
+
+    #[test]
+    fn mapped() {
+        // Platform pitch angle of -20 degrees.
+        let pitch = packet(&[0x06, 0x02, 0x80, 0x01]).platform_pitch_angle();
+        assert_eq!(pitch.unwrap().0, -20.0);
+        // The most negative value is an error indicator.
+        assert!(
+            packet(&[0x06, 0x02, 0x80, 0x00]).platform_pitch_angle().is_none()
+        );
+        // Sensor true altitude of -900 metres.
+        let altitude = packet(&[0x0F, 0x02, 0x00, 0x00]).sensor_true_altitude();
+        assert_eq!(altitude.unwrap().0, -900.0);
+        // Target track gate width of 8 pixels.
+        let width = packet(&[0x2B, 0x01, 0x04]).target_track_gate_width();
+        assert_eq!(width.unwrap().0, 8.0);
+    }
+
+    #[test]
+    fn imapb() {
+        // Target width extended of 13,898.5 metres and a zoom of 50%.
+        let mut fields = vec![0x60, 0x03];
+        fields.extend(to_imapb(0.0, 1_500_000.0, 3, 13_898.5));
+        fields.extend([0x81, 0x06, 0x02]);
+        fields.extend(to_imapb(0.0, 100.0, 2, 50.0));
+        let packet = packet(&fields);
+
+        let width = packet.target_width_extended().unwrap();
+        assert!((width.0 - 13_898.5).abs() < 0.5, "{}", width);
+        let zoom = packet.zoom_percentage().unwrap();
+        assert!((zoom.0 - 50.0).abs() < 0.01, "{}", zoom);
+    }
+
+    #[test]
+    fn unmapped() {
+        // Outside air temperature of -128 degrees and true airspeed of 200
+        // metres per second.
+        let packet = packet(&[0x27, 0x01, 0x80, 0x08, 0x01, 0xC8]);
+        assert_eq!(packet.outside_air_temperature().unwrap().0, -128.0);
+        assert_eq!(packet.platform_true_airspeed().unwrap().0, 200.0);
+        assert!(packet.platform_heading_angle().is_none());
+        assert_eq!(packet.platform_tail_number(), None);
+    }
+
+    #[test_case(Tag::PlatformHeadingAngle, 180.0; "Unsigned")]
+    #[test_case(Tag::PlatformPitchAngle, -20.0; "Signed")]
+    #[test_case(Tag::SensorLatitude, 45.123456; "Latitude")]
+    #[test_case(Tag::SensorTrueAltitude, 1234.5; "Offset")]
+    #[test_case(Tag::OutsideAirTemperature, -12.0; "Unmapped")]
+    fn round_trip(tag: Tag, value: f64) {
+        let raw = unmap(tag, value).unwrap();
+        let field = Klv::from_raw(tag.id(), &raw).unwrap();
+        let error = map(&field).unwrap() - value;
+        assert!(error.abs() < 0.2, "{:?} {}", tag, error);
+    }
+}
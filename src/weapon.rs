@@ -0,0 +1,326 @@
+//! Information from this page was gathered from the Weapon Load, Weapon Fired
+//! and Weapons Stores tag descriptions in MISB ST 0601.19.
+//!
+//! Weapon Load (tag 60) and Weapon Fired (tag 61) pack several small fields
+//! into nibbles of a single integer. Weapons Stores (tag 140) is a series of
+//! length prefixed records describing every store on the platform.
+//!
+//! The standard doesn't say how the station and substation of Weapon Fired
+//! identify a record of Weapons Stores, which numbers stores by station,
+//! hardpoint, carriage and store instead. A [`StoreLocation`] makes the
+//! mapping explicit. The default compares the station numbers directly and
+//! takes the substation to be the hardpoint, and [`releases_with`] accepts
+//! a different mapping for platforms that number their stations otherwise.
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use strum_macros::FromRepr;
+
+use crate::ErrorKind;
+use crate::cursor::Cursor;
+use crate::klv_packet::KlvPacket;
+use crate::tag::Tag;
+
+/// Weapon Load (tag 60).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WeaponLoad {
+    pub station: u8,
+    pub substation: u8,
+    pub weapon_type: u8,
+    pub weapon_variant: u8,
+}
+
+impl From<u16> for WeaponLoad {
+    fn from(value: u16) -> WeaponLoad {
+        WeaponLoad {
+            station: (value >> 12) as u8 & 0x0F,
+            substation: (value >> 8) as u8 & 0x0F,
+            weapon_type: (value >> 4) as u8 & 0x0F,
+            weapon_variant: value as u8 & 0x0F,
+        }
+    }
+}
+
+/// Weapon Fired (tag 61).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WeaponFired {
+    pub station: u8,
+    pub substation: u8,
+}
+
+impl From<u8> for WeaponFired {
+    fn from(value: u8) -> WeaponFired {
+        WeaponFired { station: value >> 4, substation: value & 0x0F }
+    }
+}
+
+/// The station and hardpoint of the stores identified by a [`WeaponFired`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StoreLocation {
+    pub station_id: usize,
+    pub hardpoint_id: usize,
+}
+
+impl From<WeaponFired> for StoreLocation {
+    /// The station is the station of Weapons Stores and the substation is the
+    /// hardpoint on that station.
+    fn from(fired: WeaponFired) -> StoreLocation {
+        StoreLocation {
+            station_id: fired.station.into(),
+            hardpoint_id: fired.substation.into(),
+        }
+    }
+}
+
+/// General status of a store from [`WeaponStore`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, FromRepr)]
+#[repr(u8)]
+pub enum GeneralStatus {
+    Off = 0,
+    Initialization = 1,
+    ReadyDegraded = 2,
+    ReadyAllUpRound = 3,
+    Launch = 4,
+    FreeFlight = 5,
+    Abort = 6,
+    MissFire = 7,
+    HangFire = 8,
+    Jettisoned = 9,
+    SteppedOver = 10,
+    NoStatusAvailable = 11,
+}
+
+/// Engagement status flags of a store from [`WeaponStore`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EngagementStatus(u8);
+
+impl EngagementStatus {
+    pub fn new(bits: u8) -> EngagementStatus {
+        EngagementStatus(bits)
+    }
+
+    /// The raw flag byte.
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Fuse functions have been set.
+    pub fn fuse_set(self) -> bool {
+        self.0 & 0x01 != 0
+    }
+
+    /// The laser code has been set.
+    pub fn laser_code_set(self) -> bool {
+        self.0 & 0x02 != 0
+    }
+
+    /// Target coordinates have been set.
+    pub fn target_set(self) -> bool {
+        self.0 & 0x04 != 0
+    }
+
+    /// The weapon is armed.
+    pub fn armed(self) -> bool {
+        self.0 & 0x08 != 0
+    }
+}
+
+/// A single record from Weapons Stores (tag 140).
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeaponStore {
+    pub station_id: usize,
+    pub hardpoint_id: usize,
+    pub carriage_id: usize,
+    pub store_id: usize,
+    /// `None` if the status is not one defined by the standard.
+    pub general_status: Option<GeneralStatus>,
+    pub engagement_status: EngagementStatus,
+    pub weapon_type: Arc<str>,
+}
+
+impl WeaponStore {
+    /// Parse the value of a Weapons Stores tag into its records.
+    pub fn from_series(bytes: &[u8]) -> Result<Vec<WeaponStore>, ErrorKind> {
+        let mut buf = Cursor::new(bytes);
+        let mut stores = Vec::new();
+
+        while (buf.position() as usize) < bytes.len() {
+            let length = KlvPacket::get_ber_value(&mut buf)
+                .map_err(|_| Self::malformed())?;
+            let start = buf.position() as usize;
+            let record = bytes
+                .get(start..start.saturating_add(length))
+                .ok_or(Self::malformed())?;
+            stores.push(Self::from_record(record)?);
+            buf.set_position((start + length) as u64);
+        }
+
+        Ok(stores)
+    }
+
+    fn from_record(bytes: &[u8]) -> Result<WeaponStore, ErrorKind> {
+        let mut buf = Cursor::new(bytes);
+        let mut next = || {
+            KlvPacket::get_ber_oid_value(&mut buf)
+                .map_err(|_| Self::malformed())
+        };
+
+        let station_id = next()?;
+        let hardpoint_id = next()?;
+        let carriage_id = next()?;
+        let store_id = next()?;
+        let general_status =
+            u8::try_from(next()?).ok().and_then(GeneralStatus::from_repr);
+        let engagement_status = EngagementStatus::new(
+            u8::try_from(next()?).map_err(|_| Self::malformed())?,
+        );
+        let weapon_type =
+            core::str::from_utf8(&bytes[buf.position() as usize..])
+                .map_err(|_| Self::malformed())?
+                .into();
+
+        Ok(WeaponStore {
+            station_id,
+            hardpoint_id,
+            carriage_id,
+            store_id,
+            general_status,
+            engagement_status,
+            weapon_type,
+        })
+    }
+
+    fn malformed() -> ErrorKind {
+        ErrorKind::MalformedValue(Tag::WeaponStores.id())
+    }
+
+    /// This store is on the station and hardpoint of `location`.
+    ///
+    /// Weapon Fired doesn't identify a carriage or store, so every store on
+    /// the hardpoint is at the location.
+    pub fn is_at(&self, location: StoreLocation) -> bool {
+        self.station_id == location.station_id
+            && self.hardpoint_id == location.hardpoint_id
+    }
+}
+
+/// A weapon release found in a sequence of KLV packets.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeaponRelease {
+    /// Precision time stamp of the packet that reported the release.
+    pub timestamp: Option<u64>,
+    pub fired: WeaponFired,
+    /// The matching store from the most recent Weapons Stores tag, if any.
+    pub store: Option<WeaponStore>,
+}
+
+/// Build a timeline of weapon releases from a sequence of packets.
+///
+/// Encoders often repeat Weapon Fired in consecutive packets so a release is
+/// only reported when the value changes. The most recently seen Weapons
+/// Stores tag is used to identify the store that was fired, using the default
+/// [`StoreLocation`] of each release.
+pub fn releases<'a>(
+    packets: impl IntoIterator<Item = &'a KlvPacket>,
+) -> Vec<WeaponRelease> {
+    releases_with(packets, StoreLocation::from)
+}
+
+/// Build a timeline of weapon releases, using `locate` to find the stores
+/// identified by each Weapon Fired.
+pub fn releases_with<'a>(
+    packets: impl IntoIterator<Item = &'a KlvPacket>,
+    locate: impl Fn(WeaponFired) -> StoreLocation,
+) -> Vec<WeaponRelease> {
+    let mut releases = Vec::new();
+    let mut stores = Vec::new();
+    let mut last_fired = None;
+
+    for packet in packets {
+        if let Some(packet_stores) = packet.weapon_stores() {
+            stores = packet_stores;
+        }
+
+        let fired = packet.weapon_fired();
+        if let Some(current) = fired.filter(|f| Some(*f) != last_fired) {
+            releases.push(WeaponRelease {
+                timestamp: packet.precision_time_stamp(),
+                fired: current,
+                store: stores
+                    .iter()
+                    .find(|s| s.is_at(locate(current)))
+                    .cloned(),
+            });
+        }
+        last_fired = fired;
+    }
+
+    releases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EngagementStatus;
+    use super::GeneralStatus;
+    use super::StoreLocation;
+    use super::WeaponFired;
+    use super::WeaponLoad;
+    use super::WeaponStore;
+
+    #[test]
+    fn nibbles() {
+        let load = WeaponLoad::from(0x1234);
+        assert_eq!(
+            load,
+            WeaponLoad {
+                station: 1,
+                substation: 2,
+                weapon_type: 3,
+                weapon_variant: 4
+            }
+        );
+        assert_eq!(
+            WeaponFired::from(0x56),
+            WeaponFired { station: 5, substation: 6 }
+        );
+    }
+
+    #[test]
+    fn stores() {
+        let mut bytes = vec![0x09, 0x01, 0x02, 0x00, 0x00, 0x05, 0x0F];
+        bytes.extend_from_slice(b"GBU");
+        bytes.extend_from_slice(&[
+            0x08, 0x02, 0x81, 0x00, 0x00, 0x00, 0x09, 0x00,
+        ]);
+        bytes.push(b'X');
+
+        let stores = WeaponStore::from_series(&bytes).unwrap();
+        assert_eq!(stores.len(), 2);
+        assert_eq!(stores[0].general_status, Some(GeneralStatus::FreeFlight));
+        assert_eq!(stores[0].engagement_status, EngagementStatus::new(0x0F));
+        assert_eq!(&*stores[0].weapon_type, "GBU");
+        assert_eq!(stores[1].hardpoint_id, 128);
+    }
+
+    #[test]
+    fn location() {
+        // Station 3, hardpoint 1, carriage 2.
+        let bytes = [0x06, 0x03, 0x01, 0x02, 0x00, 0x00, 0x00];
+        let store = WeaponStore::from_series(&bytes).unwrap().remove(0);
+        assert!(store.is_at(StoreLocation::from(WeaponFired::from(0x31))));
+        assert!(!store.is_at(StoreLocation::from(WeaponFired::from(0x32))));
+        assert!(!store.is_at(StoreLocation::from(WeaponFired::from(0x21))));
+
+        // A platform numbering the stations of Weapon Fired from zero.
+        let location = |fired: WeaponFired| StoreLocation {
+            station_id: usize::from(fired.station) + 1,
+            hardpoint_id: fired.substation.into(),
+        };
+        assert!(store.is_at(location(WeaponFired::from(0x21))));
+    }
+
+    #[test]
+    fn truncated_store() {
+        assert!(WeaponStore::from_series(&[0x05, 0x01, 0x02]).is_err());
+    }
+}